@@ -0,0 +1,215 @@
+use std::{fs, path::PathBuf, str::FromStr};
+
+use crate::{ConfigSource, Configuration};
+
+pub static CONFIG_FILE_NAME: &str = "static_atoms.toml";
+
+/**
+ * A single `key = value` assignment found in the project configuration file.
+ * Keys within a `[section]` are prefixed with the section name, like `section.key`.
+ * Arrays like `["a", "b"]` are split into one value each.
+ */
+pub struct ConfigEntry {
+    pub line: usize,
+    pub key: String,
+    pub values: Vec<String>,
+}
+
+pub fn get_config_file_path(config: &Configuration) -> PathBuf {
+    config.root.join(CONFIG_FILE_NAME)
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (index, character) in line.char_indices() {
+        match (character, quote) {
+            ('"' | '\'', None) => quote = Some(character),
+            (a, Some(b)) if a == b => quote = None,
+            ('#' | ';', None) => return &line[..index],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn parse_values(value: &str) -> Vec<String> {
+    let value = value.trim();
+    match value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    {
+        Some(array) => array
+            .split(',')
+            .map(unquote)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string())
+            .collect(),
+        None => vec![unquote(value).to_string()],
+    }
+}
+
+pub fn parse_config_file(contents: &str) -> Vec<ConfigEntry> {
+    let mut entries = Vec::new();
+    let mut section = String::default();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(section_name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = section_name.trim().to_string();
+            continue;
+        }
+
+        match line.split_once('=') {
+            Some((key, value)) => {
+                let key = key.trim().replace('-', "_");
+                entries.push(ConfigEntry {
+                    line: index + 1,
+                    key: if section.is_empty() {
+                        key
+                    } else {
+                        format!("{section}.{key}")
+                    },
+                    values: parse_values(value),
+                });
+            }
+            None => println!(
+                "{CONFIG_FILE_NAME}:{}: expected `key = value`, ignoring `{line}`",
+                index + 1
+            ),
+        }
+    }
+
+    entries
+}
+
+static CONFIG_KEYS: [&str; 12] = [
+    "out",
+    "in",
+    "depth",
+    "interval",
+    "port",
+    "base_url",
+    "feed.folder",
+    "feed.title",
+    "hide_extension",
+    "clean",
+    "verbose",
+    "permalinks",
+];
+
+fn parse_bool(entry: &ConfigEntry) -> Option<bool> {
+    match entry.values.first().map(|value| value.as_str()) {
+        Some("true") => Some(true),
+        Some("false") => Some(false),
+        _ => {
+            println!(
+                "{CONFIG_FILE_NAME}:{}: `{}` expects either `true` or `false`, ignoring",
+                entry.line, entry.key
+            );
+            None
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(entry: &ConfigEntry) -> Option<T> {
+    let number = entry.values.first().and_then(|value| value.parse().ok());
+    if number.is_none() {
+        println!(
+            "{CONFIG_FILE_NAME}:{}: `{}` does not contain a valid number, ignoring",
+            entry.line, entry.key
+        );
+    }
+    number
+}
+
+/**
+ * Applies all the entries of the configuration file onto the config. Values,
+ * that have already been set on the command line are not overwritten.
+ */
+pub fn apply_config_entries(config: &mut Configuration, entries: &[ConfigEntry]) {
+    for entry in entries {
+        let Some(key) = CONFIG_KEYS.iter().find(|key| **key == entry.key) else {
+            println!(
+                "{CONFIG_FILE_NAME}:{}: unknown key `{}`, ignoring",
+                entry.line, entry.key
+            );
+            continue;
+        };
+
+        if config.source(key) == ConfigSource::Cli {
+            continue;
+        }
+
+        let applied = match *key {
+            "out" => entry
+                .values
+                .first()
+                .map(|out| config.out = Some(PathBuf::from(out))),
+            "in" => {
+                // Multiple `in` lines add up, just like the parameter
+                if config.source(key) == ConfigSource::Default {
+                    config.input_files.clear();
+                }
+                for input in &entry.values {
+                    config.input_files.push(config.root.join(input));
+                }
+                Some(())
+            }
+            "depth" => parse_number(entry).map(|depth| config.max_depth = depth),
+            "interval" => parse_number(entry).map(|interval| config.watch_interval = interval),
+            "port" => parse_number(entry).map(|port| config.port = port),
+            "base_url" => entry
+                .values
+                .first()
+                .map(|base_url| config.base_url = Some(base_url.trim_end_matches('/').to_string())),
+            "feed.folder" => entry
+                .values
+                .first()
+                .map(|folder| config.feed = Some(config.root.join(folder))),
+            "feed.title" => entry
+                .values
+                .first()
+                .map(|title| config.feed_title = Some(title.clone())),
+            "hide_extension" => parse_bool(entry).map(|value| config.hide_extension = value),
+            "clean" => parse_bool(entry).map(|value| config.clean = value),
+            "verbose" => parse_bool(entry).map(|value| config.verbose = value),
+            "permalinks" => parse_bool(entry).map(|value| config.permalinks = value),
+            _ => None,
+        };
+
+        if applied.is_some() {
+            config.sources.insert(key, ConfigSource::File);
+        }
+    }
+}
+
+pub fn load_config_file(config: &mut Configuration) {
+    let config_file_path = get_config_file_path(config);
+    if let Ok(contents) = fs::read_to_string(&config_file_path) {
+        if config.verbose {
+            println!(
+                "[verbose] reading configuration from {}",
+                config_file_path.to_string_lossy()
+            );
+        }
+        let entries = parse_config_file(&contents);
+        apply_config_entries(config, &entries);
+    }
+}
@@ -0,0 +1,535 @@
+use std::{collections::HashMap, fs, ops::Range};
+
+use crate::{
+    Configuration,
+    dist::{
+        find_same_level, get_output_path, get_page_href, parse_token, resolve_embeds, site::Site,
+    },
+};
+
+// Blocks are constructs in the tag language, that span from an opening token to a
+// closing token, like `<## if {variable}> ... <## else> ... <## endif>`.
+// Everything in here works on both the html (`<## >`) and markdown (`[## ]`) symbols.
+
+/**
+ * Returns the text between the embed symbols of a token, like `if {variable}`
+ */
+pub fn get_token_identifier<'a>(token: &'a str, embed_symbols: (&str, char)) -> &'a str {
+    token
+        .get(embed_symbols.0.len()..(token.len() - embed_symbols.1.len_utf8()))
+        .unwrap_or("")
+        .trim()
+}
+
+fn is_keyword(identifier: &str, keyword: &str) -> bool {
+    identifier == keyword
+        || identifier
+            .strip_prefix(keyword)
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+}
+
+/**
+ * Returns the byte range of the next complete token in `contents`, starting at `offset`.
+ */
+pub fn find_next_token(
+    contents: &str,
+    offset: usize,
+    embed_symbols: (&str, char),
+) -> Option<Range<usize>> {
+    let mut offset = offset;
+    while let Some(found) = contents[offset..].find(embed_symbols.0) {
+        let start = offset + found;
+        match find_same_level(None, &contents[start..], embed_symbols.1, false) {
+            Some(token_length) => {
+                return Some(start..(start + token_length + embed_symbols.1.len_utf8()));
+            }
+            None => offset = start + embed_symbols.0.len(),
+        }
+    }
+    None
+}
+
+/**
+ * Searches for the token closing a block, that has been opened right before `contents`.
+ * Nested blocks of the same kind are skipped. Returns the byte range of the first
+ * separator token (like `else`) on the same level, and the range of the closing token.
+ */
+pub fn find_block_end(
+    contents: &str,
+    embed_symbols: (&str, char),
+    is_opening: impl Fn(&str) -> bool,
+    is_closing: impl Fn(&str) -> bool,
+    is_separator: impl Fn(&str) -> bool,
+) -> Option<(Option<Range<usize>>, Range<usize>)> {
+    let mut level = 0usize;
+    let mut separator = None;
+
+    let mut offset = 0usize;
+    while let Some(token) = find_next_token(contents, offset, embed_symbols) {
+        let identifier = get_token_identifier(&contents[token.clone()], embed_symbols);
+
+        if is_opening(identifier) {
+            level += 1;
+        } else if is_closing(identifier) {
+            if level == 0 {
+                return Some((separator, token));
+            }
+            level -= 1;
+        } else if level == 0 && separator.is_none() && is_separator(identifier) {
+            separator = Some(token.clone());
+        }
+
+        offset = token.end;
+    }
+
+    None
+}
+
+/**
+ * A value is considered true, if it is neither empty, `false` nor `0`
+ */
+pub fn is_truthy(value: &str) -> bool {
+    let value = value.trim();
+    !(value.is_empty() || value == "false" || value == "0")
+}
+
+fn resolve_operand(path: &str, operand: &str, context: &HashMap<String, String>) -> String {
+    let operand = operand.trim();
+    if let Some(variable) = operand
+        .strip_prefix('{')
+        .and_then(|operand| operand.strip_suffix('}'))
+    {
+        // Undefined variables are just false in conditions, so no need to warn here
+        return context.get(variable.trim()).cloned().unwrap_or_default();
+    }
+
+    for quote in ['"', '\''] {
+        if let Some(literal) = operand
+            .strip_prefix(quote)
+            .and_then(|operand| operand.strip_suffix(quote))
+        {
+            return literal.to_string();
+        }
+    }
+
+    println!("{path}: the operand `{operand}` is neither a {{variable}} nor a \"literal\"");
+    operand.to_string()
+}
+
+/**
+ * Evaluates the condition of an `if` block. Supported are `{variable}`, `!{variable}`,
+ * `{variable} == "literal"` and `{variable} != "literal"`.
+ */
+pub fn evaluate_condition(path: &str, condition: &str, context: &HashMap<String, String>) -> bool {
+    let condition = condition.trim();
+
+    if let Some((left, right)) = condition.split_once("==") {
+        return resolve_operand(path, left, context) == resolve_operand(path, right, context);
+    }
+
+    if let Some((left, right)) = condition.split_once("!=") {
+        return resolve_operand(path, left, context) != resolve_operand(path, right, context);
+    }
+
+    if let Some(negated) = condition.strip_prefix('!') {
+        return !is_truthy(&resolve_operand(path, negated, context));
+    }
+
+    is_truthy(&resolve_operand(path, condition, context))
+}
+
+fn parse_if_block(
+    path: String,
+    contents: &str,
+    token: Range<usize>,
+    condition: &str,
+    context: &HashMap<String, String>,
+    embed_symbols: (&str, char),
+) -> (usize, String) {
+    let body = &contents[token.end..];
+    match find_block_end(
+        body,
+        embed_symbols,
+        |identifier| is_keyword(identifier, "if"),
+        |identifier| identifier == "endif",
+        |identifier| identifier == "else",
+    ) {
+        Some((separator, end)) => {
+            let (if_body, else_body) = match separator {
+                Some(separator) => (&body[..separator.start], &body[separator.end..end.start]),
+                None => (&body[..end.start], ""),
+            };
+
+            // The chosen branch is resolved afterwards, like the rest of the contents
+            let branch = if evaluate_condition(&path, condition, context) {
+                if_body
+            } else {
+                else_body
+            };
+            (token.end + end.end, branch.to_string())
+        }
+        None => {
+            println!("{path}: `if {condition}` has no matching `endif`, replacing with empty");
+            (token.end, String::default())
+        }
+    }
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::default()];
+    let mut quoted = false;
+    for character in line.chars() {
+        match character {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::default()),
+            character => fields.last_mut().unwrap().push(character),
+        }
+    }
+    fields
+        .iter()
+        .map(|field| field.trim().to_string())
+        .collect()
+}
+
+/**
+ * Reads the items of a data file in `<root>/data`. A `.csv` file uses its first line
+ * as the names of the columns, every other file is read as one item per line.
+ */
+fn read_data_items(path: &str, config: &Configuration, name: &str) -> Vec<Vec<(String, String)>> {
+    let data_path = config.root.join("data").join(name);
+    let candidates = [
+        data_path.clone(),
+        data_path.with_extension("csv"),
+        data_path.with_extension("txt"),
+    ];
+    let Some((data_file, contents)) = candidates
+        .iter()
+        .find_map(|candidate| Some((candidate, fs::read_to_string(candidate).ok()?)))
+    else {
+        println!("{path}: the data file `{name}` does not exist in the data directory");
+        return Vec::default();
+    };
+
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    if data_file
+        .extension()
+        .is_some_and(|extension| extension == "csv")
+    {
+        let columns = lines.next().map(split_csv_line).unwrap_or_default();
+        lines
+            .map(|line| {
+                let fields = split_csv_line(line);
+                let mut item = vec![(String::default(), fields[0].clone())];
+                for (column, field) in columns.iter().zip(fields) {
+                    item.push((column.clone(), field));
+                }
+                item
+            })
+            .collect()
+    } else {
+        lines
+            .map(|line| vec![(String::default(), line.trim().to_string())])
+            .collect()
+    }
+}
+
+fn read_page_items(site: &Site, folder: &str) -> Vec<Vec<(String, String)>> {
+    site.get_pages_in_folder(folder)
+        .iter()
+        .map(|page| {
+            let href = get_page_href(site.config, page);
+            let relative_path = page.strip_prefix(&site.config.root).unwrap_or(page);
+            vec![
+                (String::default(), href.clone()),
+                ("url".to_string(), href),
+                (
+                    "path".to_string(),
+                    relative_path.to_string_lossy().to_string(),
+                ),
+                (
+                    "name".to_string(),
+                    get_output_path(site.config, page)
+                        .file_stem()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+            ]
+        })
+        .collect()
+}
+
+/**
+ * Collects the items of a `for` loop. Each item is a list of (field, value) pairs,
+ * with the empty field being the value of the loop variable itself.
+ */
+fn collect_loop_items(
+    path: &str,
+    site: &Site,
+    source: &str,
+    context: &HashMap<String, String>,
+) -> Vec<Vec<(String, String)>> {
+    let split_list = |list: &str| {
+        list.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| vec![(String::default(), item.to_string())])
+            .collect()
+    };
+
+    if source.starts_with('{') || source.starts_with('"') || source.starts_with('\'') {
+        return split_list(&resolve_operand(path, source, context));
+    }
+
+    if let Some(name) = source.strip_prefix("data:") {
+        return read_data_items(path, site.config, name.trim());
+    }
+
+    if source == "pages" {
+        return read_page_items(site, "");
+    }
+
+    if let Some(folder) = source.strip_prefix("pages:") {
+        return read_page_items(site, folder.trim());
+    }
+
+    println!(
+        "{path}: unknown list `{source}` in `for`. Use a {{variable}}, a \"literal\", data:<file> or pages"
+    );
+    Vec::default()
+}
+
+fn parse_for_block(
+    path: String,
+    site: &Site,
+    contents: &str,
+    token: Range<usize>,
+    depth: u8,
+    context: &HashMap<String, String>,
+    embed_symbols: (&str, char),
+) -> (usize, String) {
+    let arguments =
+        get_token_identifier(&contents[token.clone()], embed_symbols)["for".len()..].trim();
+    let body = &contents[token.end..];
+    let Some((_, end)) = find_block_end(
+        body,
+        embed_symbols,
+        |identifier| is_keyword(identifier, "for"),
+        |identifier| identifier == "endfor",
+        |_| false,
+    ) else {
+        println!("{path}: `for {arguments}` has no matching `endfor`, replacing with empty");
+        return (token.end, String::default());
+    };
+    let body = &body[..end.start];
+
+    let Some((variable, source)) = arguments.split_once(" in ") else {
+        println!("{path}: `for {arguments}` is malformed. Use `for item in list`");
+        return (token.end + end.end, String::default());
+    };
+    let variable = variable.trim();
+
+    let items = collect_loop_items(&path, site, source.trim(), context);
+    let mut rendered = String::default();
+    for (index, item) in items.iter().enumerate() {
+        let mut item_context = context.clone();
+        for (field, value) in item {
+            let name = if field.is_empty() {
+                variable.to_string()
+            } else {
+                format!("{variable}.{field}")
+            };
+            item_context.insert(name, value.clone());
+        }
+        item_context.insert("_INDEX".to_string(), (index + 1).to_string());
+        item_context.insert("_FIRST".to_string(), (index == 0).to_string());
+        item_context.insert("_LAST".to_string(), (index + 1 == items.len()).to_string());
+
+        // Every iteration has its own context, so the body needs to be resolved right away
+        rendered.push_str(&resolve_embeds(
+            path.clone(),
+            site,
+            body,
+            depth,
+            &item_context,
+            embed_symbols,
+        ));
+    }
+
+    (token.end + end.end, rendered)
+}
+
+/**
+ * Returns the name of a simple or parametric embed, like `card` for `card(title="x")`.
+ * Everything else, like variables and folder embeds, has no name here.
+ */
+fn get_embed_name(identifier: &str) -> Option<&str> {
+    if identifier.starts_with(['{', '/']) {
+        return None;
+    }
+    let name = identifier.split('(').next().unwrap_or("").trim();
+    (!name.is_empty() && !name.contains(|c: char| c.is_whitespace() || "[]{}".contains(c)))
+        .then_some(name)
+}
+
+fn find_embed_block_end(
+    body: &str,
+    name: &str,
+    embed_symbols: (&str, char),
+) -> Option<Range<usize>> {
+    find_block_end(
+        body,
+        embed_symbols,
+        |identifier| get_embed_name(identifier) == Some(name),
+        |identifier| identifier.strip_prefix('/').map(str::trim) == Some(name),
+        |_| false,
+    )
+    .map(|(_, end)| end)
+}
+
+/**
+ * Takes the named slots (`<## slot name>...<## endslot>`) out of the body of a block
+ * embed. Slots of block embeds nested within the body are left alone.
+ */
+fn take_named_slots(
+    path: &str,
+    body: &str,
+    embed_symbols: (&str, char),
+) -> (String, Vec<(String, String)>) {
+    let mut rest = String::default();
+    let mut slots = Vec::new();
+
+    let mut offset = 0usize;
+    while let Some(token) = find_next_token(body, offset, embed_symbols) {
+        let identifier = get_token_identifier(&body[token.clone()], embed_symbols);
+
+        if is_keyword(identifier, "slot") {
+            let slot_name = identifier["slot".len()..].trim();
+            let slot_body = &body[token.end..];
+            let Some((_, end)) = find_block_end(
+                slot_body,
+                embed_symbols,
+                |identifier| is_keyword(identifier, "slot"),
+                |identifier| identifier == "endslot",
+                |_| false,
+            ) else {
+                println!("{path}: `slot {slot_name}` has no matching `endslot`, ignoring it");
+                rest.push_str(&body[offset..token.end]);
+                offset = token.end;
+                continue;
+            };
+            rest.push_str(&body[offset..token.start]);
+            slots.push((slot_name.to_string(), slot_body[..end.start].to_string()));
+            offset = token.end + end.end;
+            continue;
+        }
+
+        // Skip over nested block embeds, their slots belong to them
+        let skip_to = get_embed_name(identifier)
+            .and_then(|name| find_embed_block_end(&body[token.end..], name, embed_symbols))
+            .map_or(token.end, |end| token.end + end.end);
+        rest.push_str(&body[offset..skip_to]);
+        offset = skip_to;
+    }
+    rest.push_str(&body[offset..]);
+
+    (rest, slots)
+}
+
+/**
+ * Resolves a block embed like `<## card(title="x")>...<## /card>`. The content in
+ * between is resolved with the current context and passed to the section as `_SLOT`.
+ * Named slots are passed as `_SLOT.name`. Returns `None`, if the token has no
+ * matching closing token, so that it's resolved like a regular embed.
+ */
+fn parse_embed_block(
+    path: String,
+    site: &Site,
+    contents: &str,
+    token: Range<usize>,
+    depth: u8,
+    context: &HashMap<String, String>,
+    embed_symbols: (&str, char),
+) -> Option<(usize, String)> {
+    let identifier = get_token_identifier(&contents[token.clone()], embed_symbols);
+    let name = get_embed_name(identifier)?;
+    let body = &contents[token.end..];
+
+    // Most embeds aren't blocks, so avoid searching the whole contents for them
+    if !body.contains(&format!("/{name}")) {
+        return None;
+    }
+    let end = find_embed_block_end(body, name, embed_symbols)?;
+
+    let (body, slots) = take_named_slots(&path, &body[..end.start], embed_symbols);
+    let mut slot_context = context.clone();
+    for (slot_name, slot_body) in slots {
+        let slot_content = resolve_embeds(
+            path.clone(),
+            site,
+            &slot_body,
+            depth,
+            context,
+            embed_symbols,
+        );
+        slot_context.insert(format!("_SLOT.{slot_name}"), slot_content);
+    }
+    let slot_content = resolve_embeds(path.clone(), site, &body, depth, context, embed_symbols);
+    slot_context.insert("_SLOT".to_string(), slot_content);
+
+    Some((
+        token.end + end.end,
+        parse_token(path, site, &contents[token], depth, &slot_context),
+    ))
+}
+
+/**
+ * Checks if the token at `token` opens (or wrongly closes) a block, and resolves it.
+ * Returns the end of the range, that should be replaced, and its replacement.
+ * If the token is not part of a block, it returns `None`.
+ */
+pub fn parse_block(
+    path: String,
+    site: &Site,
+    contents: &str,
+    token: Range<usize>,
+    depth: u8,
+    context: &HashMap<String, String>,
+    embed_symbols: (&str, char),
+) -> Option<(usize, String)> {
+    let identifier = get_token_identifier(&contents[token.clone()], embed_symbols);
+
+    if is_keyword(identifier, "if") {
+        let condition = &identifier["if".len()..];
+        return Some(parse_if_block(
+            path,
+            contents,
+            token,
+            condition,
+            context,
+            embed_symbols,
+        ));
+    }
+
+    if is_keyword(identifier, "for") {
+        return Some(parse_for_block(
+            path,
+            site,
+            contents,
+            token,
+            depth,
+            context,
+            embed_symbols,
+        ));
+    }
+
+    if ["else", "endif", "endfor", "endslot"].contains(&identifier)
+        || is_keyword(identifier, "slot")
+        || identifier.starts_with('/')
+    {
+        println!("{path}: found `{identifier}` without a matching block, replacing with empty");
+        return Some((token.end, String::default()));
+    }
+
+    parse_embed_block(path, site, contents, token, depth, context, embed_symbols)
+}
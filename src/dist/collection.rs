@@ -0,0 +1,87 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{
+    Configuration,
+    dist::{
+        get_page_href,
+        page::{get_page_date, get_page_summary, get_page_title, read_front_matter},
+        parse_single_embed,
+        site::Site,
+    },
+};
+
+// Collections are the pages within a folder of `<root>/pages`, like the posts of a
+// blog. Other than folder embeds, they are sorted by date, newest first.
+
+pub struct CollectionItem {
+    pub page: PathBuf,
+    pub date: String,
+}
+
+impl CollectionItem {
+    /**
+     * The variables describing this item, for the template it's rendered with
+     */
+    pub fn build_context(
+        &self,
+        config: &Configuration,
+        context: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        let mut item_context = context.clone();
+        item_context.extend([
+            ("_URL".to_string(), get_page_href(config, &self.page)),
+            ("_TITLE".to_string(), get_page_title(&self.page)),
+            ("_DATE".to_string(), self.date.clone()),
+            ("_SUMMARY".to_string(), get_page_summary(&self.page)),
+        ]);
+        item_context
+    }
+}
+
+/**
+ * Collects all the pages within `<root>/pages/<folder>`, except for index pages and
+ * drafts. They are sorted by their date, newest first, and by their path afterwards.
+ */
+pub fn get_collection(site: &Site, folder: &str) -> Vec<CollectionItem> {
+    let mut items: Vec<CollectionItem> = site
+        .get_pages_in_folder(folder)
+        .into_iter()
+        .filter(|page| page.is_file() && page.file_stem().is_some_and(|stem| stem != "index"))
+        .map(|page| CollectionItem {
+            date: get_page_date(&page, &read_front_matter(&page)),
+            page,
+        })
+        .collect();
+
+    items.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.page.cmp(&b.page)));
+    items
+}
+
+/**
+ * Renders the items of a collection with the section `template`, like
+ * `<## posts[..5] as post_card>`.
+ */
+pub fn render_collection(
+    path: String,
+    site: &Site,
+    items: &[CollectionItem],
+    template: &str,
+    current_depth: u8,
+    context: &HashMap<String, String>,
+) -> String {
+    let mut content = String::default();
+    for (index, item) in items.iter().enumerate() {
+        let mut item_context = item.build_context(site.config, context);
+        item_context.insert("_INDEX".to_string(), (index + 1).to_string());
+        item_context.insert("_FIRST".to_string(), (index == 0).to_string());
+        item_context.insert("_LAST".to_string(), (index + 1 == items.len()).to_string());
+        content.push_str(&parse_single_embed(
+            path.clone(),
+            site,
+            template,
+            current_depth,
+            &item_context,
+        ));
+    }
+    content
+}
@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    dist::{
+        build_page_context, escape_html, get_page_href,
+        page::{get_page_date, get_page_summary, get_page_title, read_front_matter},
+        render_page_contents,
+        site::Site,
+        write_dist_file,
+    },
+    filetype::FileType,
+};
+
+fn is_number(value: &str, length: usize) -> bool {
+    value.len() == length && value.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/**
+ * Parses the time after the date, like `10:00`, `10:00:30` or `10:00:30+02:00`, into
+ * the `10:00:30+02:00` that atom needs. Times without an offset are in UTC.
+ */
+fn parse_atom_time(time: &str) -> Option<String> {
+    let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(offset_start) => (time[..offset_start].trim(), time[offset_start..].trim()),
+        None => (time, "Z"),
+    };
+
+    let offset = if offset.eq_ignore_ascii_case("z") {
+        "Z".to_string()
+    } else {
+        let (sign, offset) = offset.split_at(1);
+        let (hours, minutes) = offset
+            .split_once(':')
+            .unwrap_or(offset.split_at(2.min(offset.len())));
+        if !(is_number(hours, 2) && is_number(minutes, 2)) {
+            return None;
+        }
+        format!("{sign}{hours}:{minutes}")
+    };
+
+    let mut parts = clock.split(':');
+    let hours = parts.next()?;
+    let minutes = parts.next()?;
+    let seconds = parts.next().unwrap_or("00");
+    let whole_seconds = seconds.split_once('.').map_or(seconds, |(whole, _)| whole);
+    if parts.next().is_some()
+        || !(is_number(hours, 2) && is_number(minutes, 2) && is_number(whole_seconds, 2))
+    {
+        return None;
+    }
+    Some(format!("{hours}:{minutes}:{seconds}{offset}"))
+}
+
+/**
+ * Atom needs the full date and time, so plain dates like `2025-04-01` start at midnight.
+ * Dates with a time like `2025-04-01 10:00` are completed to `2025-04-01T10:00:00Z`.
+ */
+pub fn to_atom_date(date: &str) -> String {
+    let (day, time) = date.split_at(10.min(date.len()));
+    let time = time.trim().trim_start_matches(['T', 't']).trim();
+    if time.is_empty() {
+        return format!("{day}T00:00:00Z");
+    }
+
+    match parse_atom_time(time) {
+        Some(time) => format!("{day}T{time}"),
+        None => {
+            println!("The time of `{date}` is not valid, using midnight instead");
+            format!("{day}T00:00:00Z")
+        }
+    }
+}
+
+/**
+ * Builds a single entry of the feed, with the page rendered as its content
+ */
+fn build_feed_entry(
+    site: &Site,
+    base_url: &str,
+    page: &Path,
+    date: &str,
+    default_context: &HashMap<String, String>,
+) -> String {
+    let front_matter = read_front_matter(page);
+    let link = escape_html(&format!("{base_url}{}", get_page_href(site.config, page)));
+    let page_context = build_page_context(site, page, default_context);
+    let (content, _) = render_page_contents(site, page, &page_context);
+
+    let mut entry = format!(
+        "  <entry>\n    <title>{}</title>\n    <link href=\"{link}\"/>\n    <id>{link}</id>\n    <updated>{}</updated>\n",
+        escape_html(&get_page_title(page)),
+        to_atom_date(date)
+    );
+    if let Some(author) = front_matter.get("author") {
+        entry.push_str(&format!(
+            "    <author><name>{}</name></author>\n",
+            escape_html(author)
+        ));
+    }
+    let summary = get_page_summary(page);
+    if !summary.is_empty() {
+        entry.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            escape_html(&summary)
+        ));
+    }
+    entry.push_str(&format!(
+        "    <content type=\"html\">{}</content>\n  </entry>\n",
+        escape_html(&content)
+    ));
+    entry
+}
+
+/**
+ * Builds an atom feed of all pages within `folder`, newest first. Index pages are
+ * left out, since they usually list the other pages.
+ */
+pub fn build_feed(
+    site: &Site,
+    base_url: &str,
+    folder: &Path,
+    pages: &[PathBuf],
+    default_context: &HashMap<String, String>,
+) -> String {
+    let mut entries: Vec<(String, &PathBuf)> = pages
+        .iter()
+        .filter(|page| {
+            page.starts_with(folder)
+                && FileType::has_valid_extension(page)
+                && page.is_file()
+                && page.file_stem().is_some_and(|stem| stem != "index")
+        })
+        .map(|page| (get_page_date(page, &read_front_matter(page)), page))
+        .collect();
+    entries.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let title = site.config.feed_title.clone().unwrap_or_else(|| {
+        folder
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    });
+    let updated = entries
+        .first()
+        .map(|(date, _)| to_atom_date(date))
+        .unwrap_or_default();
+
+    let mut feed = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n  <title>{title}</title>\n  <link href=\"{base_url}/\"/>\n  <link rel=\"self\" href=\"{base_url}/feed.xml\"/>\n  <id>{base_url}/</id>\n  <updated>{updated}</updated>\n  <author><name>{title}</name></author>\n",
+        title = escape_html(&title),
+        base_url = escape_html(base_url),
+    );
+    for (date, page) in &entries {
+        feed.push_str(&build_feed_entry(
+            site,
+            base_url,
+            page,
+            date,
+            default_context,
+        ));
+    }
+    feed.push_str("</feed>\n");
+    feed
+}
+
+/**
+ * Writes the feed.xml of the configured feed folder into the dist directory.
+ * The links in a feed have to be absolute, so it's skipped if there is no base url.
+ */
+pub fn write_feed(site: &Site, pages: &[PathBuf], default_context: &HashMap<String, String>) {
+    let Some(feed) = &site.config.feed else {
+        return;
+    };
+
+    let Some(base_url) = &site.config.base_url else {
+        println!("There is no base_url configured, skipping the feed.xml");
+        return;
+    };
+
+    println!("Writing feed.xml ...");
+    let folder = site.config.root.join(feed);
+    write_dist_file(
+        site.config,
+        Path::new("feed.xml"),
+        build_feed(site, base_url, &folder, pages, default_context),
+    );
+}
@@ -1,20 +1,40 @@
 use std::collections::HashMap;
 
-use crate::{
-    Configuration,
-    dist::{find_same_level, resolve_embeds},
+use crate::dist::{
+    escape_html, find_same_level,
+    page::strip_html_tags,
+    resolve_embeds,
+    site::Site,
+    toc::{get_unique_id, strip_html_entities},
 };
 
+/**
+ * Splits the id a markdown heading chooses for itself, like `## Heading {#my-id}`,
+ * from its text.
+ */
+pub fn split_heading_id(heading: &str) -> (&str, Option<&str>) {
+    let heading = heading.trim();
+    match heading
+        .strip_suffix('}')
+        .and_then(|heading| heading.rsplit_once("{#"))
+    {
+        Some((heading, custom_id)) if !custom_id.trim().is_empty() => {
+            (heading.trim(), Some(custom_id.trim()))
+        }
+        _ => (heading, None),
+    }
+}
+
 pub fn resolve_tokens_markdown(
     path: String,
-    config: &Configuration,
+    site: &Site,
     contents: &str,
     depth: u8,
     context: &HashMap<String, String>,
     custom_tag_type: (&str, &str),
     ignore_list_type: bool,
 ) -> String {
-    let contents = resolve_embeds(path.clone(), config, contents, depth, context, ("[##", ']'));
+    let contents = resolve_embeds(path.clone(), site, contents, depth, context, ("[##", ']'));
 
     let mut converted = String::new();
 
@@ -34,6 +54,10 @@ pub fn resolve_tokens_markdown(
         CodeBlockSpace(String),
         CodeBlockFence(usize, String),
         BlockQuote(String),
+        Table {
+            alignments: Vec<Option<&'static str>>,
+            rows: Vec<Vec<String>>,
+        },
         List {
             indent: usize,
             list_type: ListType,
@@ -44,10 +68,11 @@ pub fn resolve_tokens_markdown(
 
     let mut top_level_block = TopLevelBlock::Nothing;
     let mut prev_line_was_empty = false;
+    let mut used_ids: Vec<String> = Vec::new();
 
     fn finish_blocks(
         path: String,
-        config: &Configuration,
+        site: &Site,
         depth: u8,
         context: &HashMap<String, String>,
         converted: &mut String,
@@ -68,7 +93,7 @@ pub fn resolve_tokens_markdown(
             TopLevelBlock::BlockQuote(content) => {
                 let resolved = resolve_tokens_markdown(
                     path,
-                    config,
+                    site,
                     content,
                     depth,
                     context,
@@ -79,6 +104,39 @@ pub fn resolve_tokens_markdown(
                 converted.push_str(&resolved);
                 converted.push_str("</blockquote>")
             }
+            TopLevelBlock::Table { alignments, rows } => {
+                converted.push_str("<table>");
+                for (row_index, row) in rows.iter().enumerate() {
+                    let cell_tag = if row_index == 0 { "th" } else { "td" };
+                    match row_index {
+                        0 => converted.push_str("<thead>"),
+                        1 => converted.push_str("<tbody>"),
+                        _ => (),
+                    }
+                    converted.push_str("<tr>");
+                    // Rows are cut or filled up to the amount of columns of the header
+                    for (column, alignment) in alignments.iter().enumerate() {
+                        converted.push('<');
+                        converted.push_str(cell_tag);
+                        if let Some(alignment) = alignment {
+                            converted.push_str(&format!(" align=\"{alignment}\""));
+                        }
+                        converted.push('>');
+                        converted.push_str(&resolve_markdown_paragraph(
+                            row.get(column).map_or("", |cell| cell.as_str()),
+                        ));
+                        converted.push_str(&format!("</{cell_tag}>"));
+                    }
+                    converted.push_str("</tr>");
+                    if row_index == 0 {
+                        converted.push_str("</thead>");
+                    }
+                }
+                if rows.len() > 1 {
+                    converted.push_str("</tbody>");
+                }
+                converted.push_str("</table>")
+            }
             TopLevelBlock::List {
                 indent: _,
                 list_type,
@@ -86,9 +144,18 @@ pub fn resolve_tokens_markdown(
                 list_items,
             } => {
                 for list_item in list_items {
+                    // Task list items like `- [ ] todo` or `- [x] done` get a checkbox
+                    let (list_item, checkbox) = match list_item.get(..4) {
+                        Some("[ ] ") => (&list_item[4..], "<input type=\"checkbox\" disabled> "),
+                        Some("[x] " | "[X] ") => (
+                            &list_item[4..],
+                            "<input type=\"checkbox\" checked disabled> ",
+                        ),
+                        _ => (list_item.as_str(), ""),
+                    };
                     let resolved = resolve_tokens_markdown(
                         path.clone(),
-                        config,
+                        site,
                         list_item,
                         depth,
                         context,
@@ -100,6 +167,7 @@ pub fn resolve_tokens_markdown(
                         true,
                     );
                     converted.push_str("<li>");
+                    converted.push_str(checkbox);
                     converted.push_str(&resolved);
                     converted.push_str("</li>");
                 }
@@ -118,7 +186,8 @@ pub fn resolve_tokens_markdown(
     // resolve all embeds
     // TODO: Build a function for [## embed()], etc.
 
-    for line in contents.lines() {
+    let mut lines = contents.lines().peekable();
+    while let Some(line) = lines.next() {
         let line_no_prefix = line.trim_start();
         let trimmed_line = line_no_prefix.trim_end();
 
@@ -133,11 +202,7 @@ pub fn resolve_tokens_markdown(
             let list_type_prefix = line_no_prefix.get(0..=1);
             match list_type_prefix {
                 Some("* ") => {
-                    return if ignore_list_type {
-                        Some((ListType::UnorderedAsterisk, 0, 2))
-                    } else {
-                        Some((ListType::UnorderedAsterisk, 0, 2))
-                    };
+                    return Some((ListType::UnorderedAsterisk, 0, 2));
                 }
                 Some("+ ") => {
                     return if ignore_list_type {
@@ -175,10 +240,10 @@ pub fn resolve_tokens_markdown(
             }
 
             let parsed: Result<usize, _> = list_number_str.parse();
-            if let (Some(list_type), Ok(parsed_num)) = (list_type, parsed) {
-                if space_after_list_symbol {
-                    return Some((list_type, parsed_num, list_number_str.len() + 1));
-                }
+            if let (Some(list_type), Ok(parsed_num)) = (list_type, parsed)
+                && space_after_list_symbol
+            {
+                return Some((list_type, parsed_num, list_number_str.len() + 1));
             }
 
             None
@@ -221,7 +286,7 @@ pub fn resolve_tokens_markdown(
                     } else {
                         finish_blocks(
                             path.clone(),
-                            config,
+                            site,
                             depth,
                             context,
                             &mut converted,
@@ -242,7 +307,7 @@ pub fn resolve_tokens_markdown(
                 if prev_line_was_empty {
                     finish_blocks(
                         path.clone(),
-                        config,
+                        site,
                         depth,
                         context,
                         &mut converted,
@@ -268,7 +333,7 @@ pub fn resolve_tokens_markdown(
             if detected_list_type.is_some() {
                 finish_blocks(
                     path.clone(),
-                    config,
+                    site,
                     depth,
                     context,
                     &mut converted,
@@ -300,7 +365,7 @@ pub fn resolve_tokens_markdown(
                         let formatted_str = format!(" start=\"{list_number}\"");
                         converted.push_str(&formatted_str);
                     }
-                    converted.push_str(">");
+                    converted.push('>');
 
                     let indent = line.len() - line_no_prefix.len();
 
@@ -342,7 +407,7 @@ pub fn resolve_tokens_markdown(
             } else {
                 finish_blocks(
                     path.clone(),
-                    config,
+                    site,
                     depth,
                     context,
                     &mut converted,
@@ -362,7 +427,7 @@ pub fn resolve_tokens_markdown(
                 if trimmed_line == "```" {
                     finish_blocks(
                         path.clone(),
-                        config,
+                        site,
                         depth,
                         context,
                         &mut converted,
@@ -375,7 +440,7 @@ pub fn resolve_tokens_markdown(
             } else {
                 finish_blocks(
                     path.clone(),
-                    config,
+                    site,
                     depth,
                     context,
                     &mut converted,
@@ -415,7 +480,7 @@ pub fn resolve_tokens_markdown(
         if trimmed_line.is_empty() {
             finish_blocks(
                 path.clone(),
-                config,
+                site,
                 depth,
                 context,
                 &mut converted,
@@ -426,6 +491,64 @@ pub fn resolve_tokens_markdown(
             continue;
         }
 
+        // If the line is a row of a table, or the header of a new one
+        if let TopLevelBlock::Table {
+            alignments: _,
+            rows,
+        } = &mut top_level_block
+        {
+            if trimmed_line.contains('|') {
+                rows.push(split_table_row(trimmed_line));
+                prev_line_was_empty = false;
+                continue;
+            }
+            finish_blocks(
+                path.clone(),
+                site,
+                depth,
+                context,
+                &mut converted,
+                &mut top_level_block,
+                &custom_tag_type,
+            );
+        }
+
+        if trimmed_line.contains('|')
+            && !matches!(
+                top_level_block,
+                TopLevelBlock::List {
+                    indent: _,
+                    list_type: _,
+                    use_paragraph: _,
+                    list_items: _
+                }
+            )
+        {
+            let header = split_table_row(trimmed_line);
+            let alignments = lines
+                .peek()
+                .and_then(|next_line| parse_table_alignments(next_line.trim()))
+                .filter(|alignments| alignments.len() == header.len());
+            if let Some(alignments) = alignments {
+                finish_blocks(
+                    path.clone(),
+                    site,
+                    depth,
+                    context,
+                    &mut converted,
+                    &mut top_level_block,
+                    &custom_tag_type,
+                );
+                lines.next();
+                top_level_block = TopLevelBlock::Table {
+                    alignments,
+                    rows: vec![header],
+                };
+                prev_line_was_empty = false;
+                continue;
+            }
+        }
+
         // If the line is a horizontal line
         let mut insert_hr = false;
         for (starts_with, test_char) in [("***", '*'), ("___", '_'), ("---", '-')] {
@@ -448,7 +571,7 @@ pub fn resolve_tokens_markdown(
         if insert_hr {
             finish_blocks(
                 path.clone(),
-                config,
+                site,
                 depth,
                 context,
                 &mut converted,
@@ -461,31 +584,43 @@ pub fn resolve_tokens_markdown(
         }
 
         // If the line is a heading
-        if trimmed_line.starts_with("#") {
-            let mut header_type = 0usize;
-            for character in trimmed_line.chars() {
-                if character == '#' {
-                    header_type += 1;
-                };
-            }
+        let header_type = trimmed_line.len() - trimmed_line.trim_start_matches('#').len();
+        let heading = &trimmed_line[header_type..];
+        if (1..=6).contains(&header_type) && (heading.is_empty() || heading.starts_with(' ')) {
+            finish_blocks(
+                path.clone(),
+                site,
+                depth,
+                context,
+                &mut converted,
+                &mut top_level_block,
+                &custom_tag_type,
+            );
 
-            if header_type > 0 && header_type < 6 {
-                finish_blocks(
-                    path.clone(),
-                    config,
-                    depth,
-                    context,
-                    &mut converted,
-                    &mut top_level_block,
-                    &custom_tag_type,
-                );
-                let paragraph =
-                    resolve_markdown_paragraph(trimmed_line[(header_type + 1)..].trim());
-                converted
-                    .push_str(format!("<h{header_type}>{paragraph}</h{header_type}>").as_str());
-                prev_line_was_empty = false;
-                continue;
-            }
+            let (heading, custom_id) = split_heading_id(heading);
+
+            let paragraph = resolve_markdown_paragraph(heading);
+            let id = match custom_id {
+                Some(custom_id) => {
+                    let custom_id = escape_html(custom_id);
+                    used_ids.push(custom_id.clone());
+                    custom_id
+                }
+                None => get_unique_id(
+                    &strip_html_entities(&strip_html_tags(&paragraph)),
+                    &mut used_ids,
+                ),
+            };
+            let permalink = if site.config.permalinks {
+                format!("<a class=\"permalink\" href=\"#{id}\" aria-label=\"Permalink\">#</a>")
+            } else {
+                String::default()
+            };
+            converted.push_str(&format!(
+                "<h{header_type} id=\"{id}\">{paragraph}{permalink}</h{header_type}>"
+            ));
+            prev_line_was_empty = false;
+            continue;
         }
 
         // If the line is a block quote
@@ -498,7 +633,7 @@ pub fn resolve_tokens_markdown(
             } else {
                 finish_blocks(
                     path.clone(),
-                    config,
+                    site,
                     depth,
                     context,
                     &mut converted,
@@ -538,7 +673,7 @@ pub fn resolve_tokens_markdown(
             } else {
                 finish_blocks(
                     path.clone(),
-                    config,
+                    site,
                     depth,
                     context,
                     &mut converted,
@@ -555,7 +690,7 @@ pub fn resolve_tokens_markdown(
 
     finish_blocks(
         path,
-        config,
+        site,
         depth,
         context,
         &mut converted,
@@ -565,6 +700,107 @@ pub fn resolve_tokens_markdown(
     converted
 }
 
+/**
+ * Splits a row of a table into its cells. The pipes at the start and the end are
+ * optional, and `\|` is a pipe within a cell.
+ */
+fn split_table_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::default();
+    let mut chars = line.chars().peekable();
+    while let Some(cell_char) = chars.next() {
+        match cell_char {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(cell_char),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/**
+ * Parses the delimiter row below the header of a table, like `| :-- | :-: | --: |`,
+ * into the alignment of each column. Returns `None` if it's not a delimiter row.
+ */
+fn parse_table_alignments(line: &str) -> Option<Vec<Option<&'static str>>> {
+    if !line.contains('|') {
+        return None;
+    }
+
+    split_table_row(line)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|cell_char| cell_char == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Some("center"),
+                (true, false) => Some("left"),
+                (false, true) => Some("right"),
+                (false, false) => None,
+            })
+        })
+        .collect()
+}
+
+/**
+ * Finds all urls in a paragraph, that should become links. Either wrapped like
+ * `<https://example.com>`, or bare urls that aren't part of an html attribute or a
+ * markdown link already. Returns the start and end of each one, and its url.
+ */
+fn find_autolinks(paragraph: &str) -> Vec<(usize, usize, String)> {
+    let mut autolinks = Vec::new();
+    let mut offset = 0usize;
+    while let Some(found) = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| paragraph[offset..].find(scheme))
+        .min()
+    {
+        let start = offset + found;
+        let url_length = paragraph[start..]
+            .find(|url_char: char| url_char.is_whitespace() || url_char == '<' || url_char == '>')
+            .unwrap_or(paragraph.len() - start);
+        let mut url = &paragraph[start..(start + url_length)];
+        offset = start + url_length.max(1);
+
+        match paragraph[..start].chars().next_back() {
+            Some('<') => {
+                if paragraph[(start + url_length)..].starts_with('>') {
+                    autolinks.push((start - 1, start + url_length + 1, url.to_string()));
+                }
+            }
+            Some('"' | '\'' | '(' | '[' | '=' | '>') => (),
+            _ => {
+                // Punctuation at the end most likely belongs to the sentence
+                while let Some(last_char) = url.chars().next_back() {
+                    let unbalanced_bracket =
+                        last_char == ')' && url.matches(')').count() > url.matches('(').count();
+                    if !(unbalanced_bracket || ".,;:!?*_~'\"".contains(last_char)) {
+                        break;
+                    }
+                    url = &url[..(url.len() - 1)];
+                }
+                if url.len() > "https://".len() {
+                    autolinks.push((start, start + url.len(), url.to_string()));
+                }
+            }
+        }
+    }
+    autolinks
+}
+
 fn resolve_markdown_paragraph(paragraph: &str) -> String {
     // Relevant data
     let mut output_text = String::from(paragraph);
@@ -580,7 +816,7 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
             let self_index = elem.0;
             let neighbours = all_code_snippets
                 .iter()
-                .find(|elem| elem.0 + 1 == self_index || elem.0 - 1 == self_index);
+                .find(|elem| elem.0 + 1 == self_index || (elem.0 > 0 && elem.0 - 1 == self_index));
             neighbours.is_none()
         });
 
@@ -608,10 +844,10 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
             let mut tag = "a";
 
             let prev_char = elem.0.checked_sub(1);
-            if let Some(prev_index) = prev_char {
-                if output_text.chars().nth(prev_index).unwrap_or('.') == '!' {
-                    tag = "img";
-                }
+            if let Some(prev_index) = prev_char
+                && output_text.chars().nth(prev_index).unwrap_or('.') == '!'
+            {
+                tag = "img";
             }
 
             let found_close_bracket = find_same_level(None, &output_text[elem.0..], ']', false)?;
@@ -639,6 +875,11 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
         })
         .collect();
 
+    let link_sections: Vec<_> = all_image_links
+        .iter()
+        .map(|link| link.0..=(link.0 + link.2 + link.3 + 2))
+        .collect();
+
     // Build the actual link and image tags out of the collected info
     for link in all_image_links {
         let mut title = None;
@@ -694,12 +935,28 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
         }
     }
 
+    // autolinks, like <https://example.com> or just https://example.com
+    let mut skipped_sections = code_sections.clone();
+    for autolink in find_autolinks(&output_text) {
+        let (start, end, url) = autolink;
+        if code_sections
+            .iter()
+            .chain(&link_sections)
+            .any(|section| section.contains(&start))
+        {
+            continue;
+        }
+        let url = escape_html(&url);
+        replacements.push((start, format!("<a href=\"{url}\">{url}</a>"), end - start));
+        skipped_sections.push(start..=(end - 1));
+    }
+
     // get and filter all valid bold sections
     let all_bold_asterisks: Vec<_> = output_text
         .match_indices("**")
         .map(|elem| elem.0)
         .filter(|elem| {
-            for section in &code_sections {
+            for section in &skipped_sections {
                 if section.contains(elem) {
                     return false;
                 }
@@ -723,7 +980,7 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
                 return false;
             }
 
-            for section in &code_sections {
+            for section in &skipped_sections {
                 if section.contains(elem) {
                     return false;
                 }
@@ -745,6 +1002,22 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
         replacements.push((bold_section[1], "</strong>".into(), 2));
     }
 
+    // strikethrough sections
+    let all_strikethrough: Vec<_> = output_text
+        .match_indices("~~")
+        .map(|elem| elem.0)
+        .filter(|elem| {
+            !skipped_sections
+                .iter()
+                .any(|section| section.contains(elem))
+        })
+        .collect();
+
+    for strikethrough_section in all_strikethrough.chunks_exact(2) {
+        replacements.push((strikethrough_section[0], "<del>".into(), 2));
+        replacements.push((strikethrough_section[1], "</del>".into(), 2));
+    }
+
     // emphasize sections
     let all_em: Vec<_> = output_text.match_indices("*").map(|elem| elem.0).collect();
     let all_em_underscore: Vec<_> = output_text.match_indices("_").map(|elem| elem.0).collect();
@@ -753,6 +1026,12 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
 
     all_em_deduped.retain(|elem| {
         let self_index = *elem;
+        if skipped_sections
+            .iter()
+            .any(|section| section.contains(&self_index))
+        {
+            return false;
+        }
         let neighbours = all_em.iter().find(|neighbour| {
             **neighbour + 1 == self_index || (**neighbour > 0 && **neighbour - 1 == self_index)
         });
@@ -761,6 +1040,12 @@ fn resolve_markdown_paragraph(paragraph: &str) -> String {
 
     all_em_underscore_deduped.retain(|elem| {
         let self_index = *elem;
+        if skipped_sections
+            .iter()
+            .any(|section| section.contains(&self_index))
+        {
+            return false;
+        }
         let prev_char = if self_index > 0 {
             output_text.chars().nth(self_index - 1).unwrap_or('.')
         } else {
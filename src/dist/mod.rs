@@ -2,17 +2,42 @@ use std::{
     collections::HashMap,
     ffi::OsStr,
     fs, io,
-    path::{Path, PathBuf},
-    usize,
+    path::{Component, Path, PathBuf},
 };
 
+pub mod blocks;
+pub mod collection;
+pub mod feed;
 pub mod markdown;
+pub mod navigation;
+pub mod page;
+pub mod pagination;
+pub mod site;
+pub mod sitemap;
+pub mod tags;
+pub mod toc;
+
+use crate::{
+    Configuration,
+    dist::{
+        blocks::parse_block,
+        collection::{get_collection, render_collection},
+        feed::write_feed,
+        markdown::resolve_tokens_markdown,
+        navigation::{build_sibling_context, resolve_navigation_variable},
+        page::{format_date, get_page_title, is_draft, read_front_matter, split_front_matter},
+        pagination::{get_page_items, process_paginated_page},
+        site::Site,
+        sitemap::write_sitemap,
+        tags::{build_tags_list, get_page_tags, write_tag_pages},
+        toc::{TOC_PLACEHOLDER, add_heading_ids, build_toc},
+    },
+    filetype::FileType,
+};
 
-use crate::{Configuration, filetype::FileType};
-
-fn get_dist_path(config: &Configuration) -> PathBuf {
+pub fn get_dist_path(config: &Configuration) -> PathBuf {
     match &config.out {
-        Some(dist) => dist.clone(),
+        Some(dist) => config.root.join(dist),
         None => config.root.clone().join("dist"),
     }
 }
@@ -43,38 +68,83 @@ fn copy_dir(
     Ok(())
 }
 
-pub fn get_pages(config: &Configuration) -> Vec<PathBuf> {
-    fn read_folder_layer(path: PathBuf, pages_vec: &mut Vec<PathBuf>) {
-        for entry in fs::read_dir(&path).unwrap_or_else(|_| panic!("Wasn't able to completely go through the input directory. Does it exist and are you allowed to open it? Path: {}", path.to_string_lossy())).flatten() {
-                if let Ok(filetype) = entry.file_type() {
-                        if filetype.is_file() {
-                            pages_vec.push(entry.path());
-                        }
+fn read_folder_layer(path: PathBuf, pages_vec: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(&path).unwrap_or_else(|_| panic!("Wasn't able to completely go through the input directory. Does it exist and are you allowed to open it? Path: {}", path.to_string_lossy())).flatten() {
+            if let Ok(filetype) = entry.file_type() {
+                    if filetype.is_file() {
+                        pages_vec.push(entry.path());
+                    }
 
-                        if filetype.is_dir() {
-                            read_folder_layer(entry.path(), pages_vec);
-                        }
+                    if filetype.is_dir() {
+                        read_folder_layer(entry.path(), pages_vec);
                     }
-            }
-    }
+                }
+        }
+}
 
+pub fn get_pages(config: &Configuration) -> Vec<PathBuf> {
     let mut pages_vec: Vec<PathBuf> = Default::default();
 
     if !config.input_files.is_empty() {
         for input_file in &config.input_files {
+            // Inputs are relative to the root, and pages outside of it have no place
+            // in the dist, so they are skipped
+            let input_file = config.root.join(input_file);
+            if !input_file.starts_with(&config.root)
+                || input_file
+                    .components()
+                    .any(|component| component == Component::ParentDir)
+            {
+                println!(
+                    "`{}` is outside of the project root, skipping it",
+                    input_file.to_string_lossy()
+                );
+                continue;
+            }
             if input_file.is_dir() {
                 read_folder_layer(input_file.clone(), &mut pages_vec);
             }
             if input_file.is_file() {
-                pages_vec.push(input_file.clone());
+                pages_vec.push(input_file);
             }
         }
     } else {
         pages_vec.push(config.root.clone().join("index.html"));
         let pages_path = config.root.clone().join("pages");
+        if pages_path.is_dir() {
+            read_folder_layer(pages_path, &mut pages_vec);
+        } else {
+            println!(
+                "There is no pages directory at `{}`, skipping it. Use `static_atoms init` to create a new project",
+                pages_path.to_string_lossy()
+            );
+        }
+    }
+
+    pages_vec.retain(|page| {
+        let draft = is_draft(&read_front_matter(page));
+        if draft && config.verbose {
+            println!("[verbose] skipping draft {}", page.to_string_lossy());
+        }
+        !draft
+    });
+
+    pages_vec.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
+    pages_vec
+}
+
+/**
+ * All the pages within `<root>/pages` except for drafts, no matter if they are
+ * selected with `--in`. Collections always list all of them.
+ */
+pub fn get_folder_pages(config: &Configuration) -> Vec<PathBuf> {
+    let mut pages_vec: Vec<PathBuf> = Default::default();
+    let pages_path = config.root.join("pages");
+    if pages_path.is_dir() {
         read_folder_layer(pages_path, &mut pages_vec);
     }
 
+    pages_vec.retain(|page| !is_draft(&read_front_matter(page)));
     pages_vec.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
     pages_vec
 }
@@ -84,10 +154,10 @@ pub fn run_dist(config: &Configuration) {
     let root_path = config.root.clone().join("root");
     let dist_path = get_dist_path(config);
 
-    if config.clean {
+    if config.clean && dist_path.exists() {
         println!("Clearing old dist directory...");
         fs::remove_dir_all(&dist_path)
-            .expect("Wasn't able to remove the pages directory. Does it exist?");
+            .expect("Wasn't able to remove the pages directory. Do you have the permissions?");
     }
 
     fs::create_dir_all(&dist_path)
@@ -95,7 +165,7 @@ pub fn run_dist(config: &Configuration) {
 
     // Copy the root files over
     println!("Copy project root files...");
-    if copy_dir(config, root_path, &dist_path).is_err() {
+    if root_path.is_dir() && copy_dir(config, root_path, &dist_path).is_err() {
         println!(
             "Something went wrong, when copying root files over to {}",
             dist_path.to_string_lossy()
@@ -104,7 +174,7 @@ pub fn run_dist(config: &Configuration) {
 
     // Copy all the media over
     println!("Copy all Media...");
-    if copy_dir(config, media_path, dist_path.join("media")).is_err() {
+    if media_path.is_dir() && copy_dir(config, media_path, dist_path.join("media")).is_err() {
         println!(
             "Something went wrong, when copying the media over to {}",
             dist_path.join("media").to_string_lossy()
@@ -115,19 +185,95 @@ pub fn run_dist(config: &Configuration) {
     println!("Building global Context");
     let pages = get_pages(config);
     let default_context = build_default_context(config, &pages);
+    let site = Site::new(config, &pages);
 
     // Go through the pages directory
-    for page in pages {
-        if FileType::has_valid_extension(&page) {
-            process_page(config, page, &default_context);
+    for page in &pages {
+        if FileType::has_valid_extension(page) && page.is_file() {
+            process_page(&site, page.clone(), &default_context);
         }
     }
 
-    process_page(
-        config,
-        config.root.clone().join("index.html"),
-        &default_context,
-    );
+    let index_path = config.root.clone().join("index.html");
+    if index_path.is_file() {
+        process_page(&site, index_path, &default_context);
+    } else {
+        println!(
+            "There is no index.html at `{}`, skipping it",
+            index_path.to_string_lossy()
+        );
+    }
+
+    write_sitemap(config, &pages);
+    write_feed(&site, &pages, &default_context);
+    write_tag_pages(&site, &pages, &default_context);
+}
+
+/**
+ * The path of a page within the dist directory. Pages are always written as html,
+ * with the extension being omitted for `hide_extension`, except for index files.
+ */
+pub fn get_output_path(config: &Configuration, page: &Path) -> PathBuf {
+    let mut relative_path = page
+        .strip_prefix(&config.root)
+        .map(|path| path.to_path_buf())
+        .unwrap_or_else(|_| {
+            panic!(
+                "The page `{}` is outside of the project root, and can't be written to the dist",
+                page.to_string_lossy()
+            )
+        });
+
+    let mut file_stem = relative_path.file_stem().unwrap_or_default().to_os_string();
+    relative_path.pop();
+
+    // If the file is an index file, or the config is set to show extensions, add them
+    if file_stem == "index" || !config.hide_extension {
+        file_stem.push(".html");
+    }
+
+    relative_path.push(file_stem);
+    relative_path
+}
+
+/**
+ * The absolute link to a file within the dist directory. Index files are linked by
+ * their folder.
+ */
+pub fn get_output_href(output_path: &Path) -> String {
+    let href_path = if output_path.file_name() == Some(OsStr::new("index.html")) {
+        output_path.parent().unwrap_or(Path::new(""))
+    } else {
+        output_path
+    };
+    format!("/{}", href_path.to_string_lossy())
+}
+
+/**
+ * The absolute link to a page on the website
+ */
+pub fn get_page_href(config: &Configuration, page: &Path) -> String {
+    get_output_href(&get_output_path(config, page))
+}
+
+/**
+ * The values of the context, that depend on where a page is written to within the
+ * dist directory: its link, its file name and the prefix leading back to the root.
+ */
+pub fn build_output_context(output_path: &Path) -> [(String, String); 3] {
+    let depth = output_path.components().count().saturating_sub(1);
+    [
+        ("_URL".to_string(), get_output_href(output_path)),
+        (
+            "_FILENAME".to_string(),
+            output_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        ),
+        ("_DEPTH_PREFIX".to_string(), "../".repeat(depth)),
+    ]
 }
 
 pub fn build_pages_context(config: &Configuration, input_pages: &[PathBuf]) -> String {
@@ -135,37 +281,10 @@ pub fn build_pages_context(config: &Configuration, input_pages: &[PathBuf]) -> S
     pages_string.push_str("<ul class=\"siteindex\">");
 
     for page in input_pages {
-        let mut relative_path = page
-            .strip_prefix(&config.root)
-            .map(|path| path.to_path_buf())
-            .unwrap_or_else(|err| {
-                println!("Wasn't able to resolve path when building pages context. {err}");
-                page.to_path_buf()
-            });
-
-        let mut filestem: String = relative_path
-            .file_stem()
-            .map(|os_str| os_str.to_str().unwrap_or(""))
-            .unwrap_or("")
-            .into();
-        relative_path.pop();
-
-        if filestem == "index" || !config.hide_extension {
-            filestem += ".html";
-        }
-
-        let relative_path_href = if filestem != "index.html" {
-            relative_path.push(filestem);
-            relative_path.to_string_lossy().to_string()
-        } else {
-            let path_string = relative_path.to_string_lossy().to_string();
-            relative_path.push(filestem);
-            path_string
-        };
-
-        let relative_path_label = relative_path.to_string_lossy().to_string();
+        let relative_path_href = get_page_href(config, page);
+        let relative_path_label = get_output_path(config, page).to_string_lossy().to_string();
         pages_string.push_str(&format!(
-            "<li><a href=\"/{relative_path_href}\">{relative_path_label}</a></li>"
+            "<li><a href=\"{relative_path_href}\">{relative_path_label}</a></li>"
         ));
     }
 
@@ -198,53 +317,103 @@ pub fn build_default_context(
     ])
 }
 
-pub fn resolve_tokens_from_path(
-    path_string: String,
-    path: &PathBuf,
-    config: &Configuration,
-    depth: u8,
-    context: &HashMap<String, String>,
-) -> Option<String> {
-    let relative_path = path
-        .strip_prefix(config.root.clone())
-        .unwrap_or(path.as_path());
+/**
+ * Extends the default context with the values, that belong to a single page,
+ * like its link, its title and the prefix leading back to the root of the website.
+ */
+pub fn build_page_context(
+    site: &Site,
+    page: &Path,
+    default_context: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let relative_path = page.strip_prefix(&site.config.root).unwrap_or(page);
+    let modified = fs::metadata(page)
+        .and_then(|metadata| metadata.modified())
+        .map(format_date)
+        .unwrap_or_default();
+
+    let mut context = default_context.clone();
+    context.extend([
+        (
+            "_PATH".to_string(),
+            relative_path.to_string_lossy().to_string(),
+        ),
+        ("_TITLE".to_string(), get_page_title(page)),
+        ("_MODIFIED".to_string(), modified),
+        (
+            "_TAGS".to_string(),
+            build_tags_list(site.config, &get_page_tags(&read_front_matter(page))),
+        ),
+    ]);
+    context.extend(build_output_context(&get_output_path(site.config, page)));
+    context.extend(build_sibling_context(site, page));
+    context
+}
 
-    for filetype in crate::filetype::FileType::get_valid_filetypes() {
+/**
+ * Finds the file belonging to a page, section or layout path. If the path doesn't
+ * already end in a valid extension, all of them are tried one after another.
+ */
+pub fn find_source_file(path: &Path) -> Option<(PathBuf, &'static FileType)> {
+    FileType::get_valid_filetypes().iter().find_map(|filetype| {
         let path_with_extension =
-            if path.extension().and_then(OsStr::to_str) != Some(filetype.extension().into()) {
-                let mut new_path = path.clone();
+            if path.extension().and_then(OsStr::to_str) != Some(filetype.extension()) {
+                let mut new_path = path.to_path_buf();
                 new_path.add_extension(filetype.extension());
                 new_path
             } else {
-                path.clone()
+                path.to_path_buf()
             };
-        if let Ok(contents) = fs::read_to_string(path_with_extension) {
-            return Some(filetype.convert_content(
-                relative_path.to_string_lossy().to_string() + " >> " + &path_string,
-                &contents,
-                config,
-                depth + 1,
-                context,
-            ));
+        path_with_extension
+            .is_file()
+            .then_some((path_with_extension, filetype))
+    })
+}
+
+pub fn resolve_tokens_from_path(
+    path_string: String,
+    path: &Path,
+    site: &Site,
+    depth: u8,
+    context: &HashMap<String, String>,
+) -> Option<String> {
+    let relative_path = path.strip_prefix(site.config.root.clone()).unwrap_or(path);
+
+    let (path_with_extension, filetype) = find_source_file(path)?;
+    let contents = fs::read_to_string(path_with_extension).ok()?;
+
+    // The front matter is only part of the context of this file and its embeds
+    let (local_context, contents) = match split_front_matter(&contents) {
+        Some((front_matter, contents)) => {
+            let mut local_context = context.clone();
+            local_context.extend(front_matter);
+            (Some(local_context), contents)
         }
-    }
+        None => (None, contents.as_str()),
+    };
 
-    None
+    Some(filetype.convert_content(
+        relative_path.to_string_lossy().to_string() + " >> " + &path_string,
+        contents,
+        site,
+        depth + 1,
+        local_context.as_ref().unwrap_or(context),
+    ))
 }
 
 pub fn resolve_tokens_html(
     path: String,
-    config: &Configuration,
+    site: &Site,
     contents: &str,
     depth: u8,
     context: &HashMap<String, String>,
 ) -> String {
-    resolve_embeds(path, config, contents, depth, context, ("<##", '>'))
+    resolve_embeds(path, site, contents, depth, context, ("<##", '>'))
 }
 
 pub fn resolve_embeds(
     path: String,
-    config: &Configuration,
+    site: &Site,
     contents_str: &str,
     depth: u8,
     context: &HashMap<String, String>,
@@ -262,22 +431,23 @@ pub fn resolve_embeds(
             break;
         }
 
-        if config.verbose {
+        if site.config.verbose {
             println!(
                 "[verbose] {path}: found next '{}' at index {index}",
                 embed_symbols.0
             )
         };
         if let Some(index_end) = find_same_level(None, &contents[index..], embed_symbols.1, false) {
-            if config.verbose {
+            if site.config.verbose {
                 println!(
                     "[verbose] {path}: found matching '{}' after {index_end} char(s)",
                     embed_symbols.1
                 )
             };
-            let new_content = if depth < config.max_depth {
-                let internal_contents = &contents[index..(index + index_end + 1)];
-                if config.verbose {
+            let token_end = index + index_end + 1;
+            let (replace_end, new_content) = if depth < site.config.max_depth {
+                let internal_contents = &contents[index..token_end];
+                if site.config.verbose {
                     let internal_contents_short = if internal_contents.len() > 50 {
                         let end_pos = internal_contents.char_indices().nth_back(7).unwrap().0;
                         String::from(&internal_contents[..35])
@@ -290,15 +460,30 @@ pub fn resolve_embeds(
                         "[verbose] {path}: will parse the contents \"{internal_contents_short}\"",
                     )
                 };
-                parse_token(path.clone(), config, internal_contents, depth, context)
+                // Blocks like `if` span multiple tokens, and replace everything up to their end
+                parse_block(
+                    path.clone(),
+                    site,
+                    &contents,
+                    index..token_end,
+                    depth,
+                    context,
+                    embed_symbols,
+                )
+                .unwrap_or_else(|| {
+                    (
+                        token_end,
+                        parse_token(path.clone(), site, internal_contents, depth, context),
+                    )
+                })
             } else {
                 println!(
                     "Surpassed max recursion depth of {}. Replacing deeper embeds with space",
-                    config.max_depth
+                    site.config.max_depth
                 );
-                String::default()
+                (token_end, String::default())
             };
-            contents.replace_range(index..(index + index_end + 1), &new_content);
+            contents.replace_range(index..replace_end, &new_content);
         }
 
         last_token_index = index;
@@ -309,23 +494,12 @@ pub fn resolve_embeds(
     contents
 }
 
-pub fn write_contents(config: &Configuration, page: PathBuf, contents: String) {
+/**
+ * Writes a file into the dist directory, or prints it for a dry run
+ */
+pub fn write_dist_file(config: &Configuration, relative_path: &Path, contents: String) {
     if config.write {
-        let relative_path = page
-            .strip_prefix(config.root.clone())
-            .expect("Wasnt able to get relative Path");
-        let dist_path = get_dist_path(config);
-        let mut absolute_path = dist_path.join(relative_path);
-
-        let mut file_stem = absolute_path.file_stem().unwrap_or_default().to_os_string();
-        absolute_path.pop();
-
-        // If the file is an index file, or the config is set to show extensions, add them
-        if file_stem == "index" || !config.hide_extension {
-            file_stem.push(".html");
-        }
-
-        absolute_path.push(file_stem);
+        let absolute_path = get_dist_path(config).join(relative_path);
 
         let mut ancestors = absolute_path.ancestors();
         ancestors.next();
@@ -354,24 +528,121 @@ pub fn write_contents(config: &Configuration, page: PathBuf, contents: String) {
     }
 }
 
-pub fn process_page(
-    config: &Configuration,
-    page: PathBuf,
-    default_context: &HashMap<String, String>,
-) {
-    let relative_path = page
-        .strip_prefix(config.root.clone())
-        .unwrap_or(page.as_path());
+pub fn write_contents(config: &Configuration, page: PathBuf, contents: String) {
+    write_dist_file(config, &get_output_path(config, &page), contents)
+}
+
+/**
+ * Renders the contents of a page without its layouts. Afterwards, every heading gets
+ * an id and the table of contents is inserted. Returns the contents and `_TOC`.
+ */
+pub fn render_page_contents(
+    site: &Site,
+    page: &Path,
+    page_context: &HashMap<String, String>,
+) -> (String, String) {
+    let relative_path = page.strip_prefix(&site.config.root).unwrap_or(page);
     let path_string = relative_path.to_string_lossy();
-    println!("Transforming {path_string} ...");
-    let contents = resolve_tokens_from_path(path_string.into(), &page, config, 0, default_context)
+    let mut context = page_context.clone();
+    context.insert("_TOC".to_string(), TOC_PLACEHOLDER.to_string());
+
+    let contents = resolve_tokens_from_path(path_string.into(), page, site, 0, &context)
         .unwrap_or_else(|| {
             panic!(
                 "Wasn't able to build page, since no page content could be generated for {}",
                 page.to_string_lossy()
             );
         });
-    write_contents(config, page, contents)
+
+    let (contents, headings) = add_heading_ids(&contents);
+    let toc = build_toc(&headings);
+    (contents.replace(TOC_PLACEHOLDER, &toc), toc)
+}
+
+/**
+ * Renders a page with its layouts, using the context of that page
+ */
+pub fn render_page(site: &Site, page: &Path, page_context: &HashMap<String, String>) -> String {
+    let (contents, toc) = render_page_contents(site, page, page_context);
+    let mut layout_context = page_context.clone();
+    layout_context.insert("_TOC".to_string(), toc);
+    apply_layouts(site, page, contents, &layout_context)
+}
+
+pub fn process_page(site: &Site, page: PathBuf, default_context: &HashMap<String, String>) {
+    let relative_path = page
+        .strip_prefix(site.config.root.clone())
+        .unwrap_or(page.as_path());
+    println!("Transforming {} ...", relative_path.to_string_lossy());
+    let page_context = build_page_context(site, &page, default_context);
+
+    // Paginated pages are written once for every page of their collection
+    if let Some(collection) = read_front_matter(&page).get("paginate") {
+        process_paginated_page(site, &page, collection, &page_context);
+        return;
+    }
+
+    let contents = render_page(site, &page, &page_context);
+    write_contents(site.config, page, contents)
+}
+
+/**
+ * Wraps the rendered page into the layout named by `layout` in its front matter.
+ * The layout file lives in `<root>/layouts` and inserts the page with `{_CONTENT}`.
+ * Layouts can declare a layout themselves, to be wrapped again.
+ */
+pub fn apply_layouts(
+    site: &Site,
+    page: &Path,
+    mut contents: String,
+    context: &HashMap<String, String>,
+) -> String {
+    let page_path = page.strip_prefix(&site.config.root).unwrap_or(page);
+    let mut front_matter = read_front_matter(page);
+    let mut layout_context = context.clone();
+
+    for _ in 0..site.config.max_depth {
+        let Some(layout) = front_matter.remove("layout") else {
+            return contents;
+        };
+
+        let Some((layout_path, _)) =
+            find_source_file(&site.config.root.join("layouts").join(&layout))
+        else {
+            println!(
+                "The layout `{layout}` used by {} does not exist in the layouts directory, ignoring",
+                page_path.to_string_lossy()
+            );
+            return contents;
+        };
+
+        if site.config.verbose {
+            println!(
+                "[verbose] {}: wrapping into layout {}",
+                page_path.to_string_lossy(),
+                layout_path.to_string_lossy()
+            );
+        }
+
+        layout_context.extend(front_matter);
+        layout_context.insert("_CONTENT".to_string(), contents);
+        contents = resolve_tokens_from_path(
+            page_path.to_string_lossy().to_string(),
+            &layout_path,
+            site,
+            0,
+            &layout_context,
+        )
+        .unwrap_or_default();
+        front_matter = read_front_matter(&layout_path);
+    }
+
+    println!(
+        "Surpassed max recursion depth of {} while applying layouts to {}",
+        site.config.max_depth,
+        page_path.to_string_lossy()
+    );
+    contents
 }
 
 pub fn find_same_level(
@@ -395,33 +666,27 @@ pub fn find_same_level(
                 layers.push(a);
             }
         }
-        ')' => {
-            if layers.last().is_some() && layers.last().unwrap().eq(&'(') {
-                layers.pop();
-            }
+        ')' if layers.last().is_some() && layers.last().unwrap().eq(&'(') => {
+            layers.pop();
         }
-        ']' => {
-            if layers.last().is_some() && layers.last().unwrap().eq(&'[') {
-                layers.pop();
-            }
+        ']' if layers.last().is_some() && layers.last().unwrap().eq(&'[') => {
+            layers.pop();
         }
-        '>' => {
-            if layers.last().is_some() && layers.last().unwrap().eq(&'<') {
-                layers.pop();
-            }
+        '>' if layers.last().is_some() && layers.last().unwrap().eq(&'<') => {
+            layers.pop();
         }
         _ => (),
     };
 
     if test_first {
-        for (index, character) in input.chars().enumerate() {
+        for (index, character) in input.char_indices() {
             if character.eq(&test_char) && layers.is_empty() {
                 return Some(index);
             };
             level_test(character, &mut layers);
         }
     } else {
-        for (index, character) in input.chars().enumerate() {
+        for (index, character) in input.char_indices() {
             level_test(character, &mut layers);
             if character.eq(&test_char) && layers.is_empty() {
                 return Some(index);
@@ -434,19 +699,35 @@ pub fn find_same_level(
 
 pub fn parse_token(
     path: String,
-    config: &Configuration,
+    site: &Site,
     token: &str,
     current_depth: u8,
     context: &HashMap<String, String>,
 ) -> String {
     let embed_identifier = token[3..token.len() - 1].trim();
 
+    // Check if it is a variable first, since its default value may contain any symbol
+    if let Some(variable) = embed_identifier.strip_prefix('{') {
+        match variable.rfind('}') {
+            Some(close_index) => {
+                return parse_variable(
+                    path,
+                    site,
+                    &variable[..close_index],
+                    current_depth,
+                    context,
+                );
+            }
+            None => println!("variable identifier `{embed_identifier}` is incomplete"),
+        }
+    }
+
     // Check if it might be a folder embed
     match (embed_identifier.find("["), embed_identifier.find("]")) {
         (Some(open_index), Some(close_index)) => {
             return parse_folder_embed(
                 path,
-                config,
+                site,
                 embed_identifier,
                 current_depth,
                 (open_index, close_index),
@@ -467,7 +748,7 @@ pub fn parse_token(
         (Some(open_index), Some(close_index)) => {
             return parse_parametric_embed(
                 path,
-                config,
+                site,
                 embed_identifier,
                 current_depth,
                 (open_index, close_index),
@@ -480,24 +761,18 @@ pub fn parse_token(
         _ => (),
     }
 
-    // Check if it is a variable
-    match (embed_identifier.find("{"), embed_identifier.find("}")) {
-        (Some(0), Some(close_index)) => {
-            return parse_variable(&embed_identifier[1..close_index], context);
-        }
-        (Some(_), Some(_)) | (Some(_), None) | (None, Some(_)) => {
-            println!("variable identifier `{embed_identifier}` is incomplete or malformed");
-        }
-        _ => (),
+    // Check if it is a malformed variable
+    if embed_identifier.contains(['{', '}']) {
+        println!("variable identifier `{embed_identifier}` is incomplete or malformed");
     }
 
     // If none of them worked, it's most likely a simple embed
-    parse_single_embed(path, config, embed_identifier, current_depth, context)
+    parse_single_embed(path, site, embed_identifier, current_depth, context)
 }
 
 pub fn parse_parametric_embed(
     path: String,
-    config: &Configuration,
+    site: &Site,
     component: &str,
     current_depth: u8,
     brackets: (usize, usize),
@@ -506,7 +781,7 @@ pub fn parse_parametric_embed(
     if brackets.1 - brackets.0 == 1 {
         parse_single_embed(
             path,
-            config,
+            site,
             component[..brackets.0].trim(),
             current_depth,
             context,
@@ -542,7 +817,7 @@ pub fn parse_parametric_embed(
 
             let value = &variables_string[(next_string_open + 1)..next_string_close];
 
-            if config.verbose {
+            if site.config.verbose {
                 println!(
                     "[verbose] {path}: adding value for \'{}\' context. Content length: {}",
                     variable_name,
@@ -562,7 +837,7 @@ pub fn parse_parametric_embed(
 
         parse_single_embed(
             path,
-            config,
+            site,
             component[..brackets.0].trim(),
             current_depth,
             &local_context,
@@ -570,26 +845,165 @@ pub fn parse_parametric_embed(
     }
 }
 
-pub fn parse_variable(component: &str, context: &HashMap<String, String>) -> String {
-    match context.get(component).cloned() {
-        Some(variable) => variable,
-        None => {
-            println!("The variable `{component}` is undefined, replacing with empty space.");
-            Default::default()
+/**
+ * Splits a variable embed like `title | "Untitled"` at every `|`, that is not
+ * part of a quoted string.
+ */
+pub fn split_variable_pipes(component: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut part_start = 0;
+    for (index, character) in component.char_indices() {
+        match (character, quote) {
+            ('"' | '\'', None) => quote = Some(character),
+            (a, Some(b)) if a == b => quote = None,
+            ('|', None) => {
+                parts.push(component[part_start..index].trim());
+                part_start = index + 1;
+            }
+            _ => (),
         }
     }
+    parts.push(component[part_start..].trim());
+    parts
 }
 
-pub fn parse_folder_embed(
+fn unquote_literal(value: &str) -> Option<&str> {
+    ['"', '\''].iter().find_map(|quote| {
+        value
+            .strip_prefix(*quote)
+            .and_then(|value| value.strip_suffix(*quote))
+    })
+}
+
+/**
+ * Turns a text into something, that can be used in urls and as an id, like
+ * `Hello, World!` into `hello-world`
+ */
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::default();
+    for character in text.chars() {
+        if character.is_alphanumeric() {
+            slug.extend(character.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+pub fn apply_variable_filter(
     path: String,
-    config: &Configuration,
+    site: &Site,
+    filter: &str,
+    value: &str,
+    current_depth: u8,
+    context: &HashMap<String, String>,
+) -> String {
+    let (name, argument) = match filter.split_once('(') {
+        Some((name, argument)) => (name.trim(), Some(argument.trim_end_matches(')').trim())),
+        None => (filter, None),
+    };
+
+    match (name, argument) {
+        ("upper", None) => value.to_uppercase(),
+        ("lower", None) => value.to_lowercase(),
+        ("trim", None) => value.trim().to_string(),
+        ("escape", None) => escape_html(value),
+        ("slugify", None) => slugify(value),
+        ("markdown", None) => resolve_tokens_markdown(
+            path,
+            site,
+            value,
+            current_depth + 1,
+            context,
+            ("<p>", "</p>"),
+            false,
+        ),
+        ("truncate", Some(length)) => match length.parse::<usize>() {
+            Ok(length) if value.chars().count() > length => {
+                let truncated: String = value.chars().take(length).collect();
+                truncated.trim_end().to_string() + "…"
+            }
+            Ok(_) => value.to_string(),
+            Err(_) => {
+                println!("The filter `{filter}` does not contain a valid length. Ignoring it.");
+                value.to_string()
+            }
+        },
+        _ => {
+            println!("The filter `{filter}` is unknown. Ignoring it.");
+            value.to_string()
+        }
+    }
+}
+
+/**
+ * Resolves a variable embed like `title | "Untitled" | upper`. The parts after the
+ * name are applied from left to right, with quoted literals being default values.
+ */
+pub fn parse_variable(
+    path: String,
+    site: &Site,
     component: &str,
     current_depth: u8,
-    brackets: (usize, usize),
     context: &HashMap<String, String>,
 ) -> String {
-    // Determine the item amount
-    let mut elem_count = if brackets.1 - brackets.0 == 1 {
+    let mut parts = split_variable_pipes(component).into_iter();
+    let name = parts.next().unwrap_or_default();
+    let mut value =
+        resolve_navigation_variable(site, name, context).or_else(|| context.get(name).cloned());
+
+    for part in parts {
+        // A quoted literal is the default for an undefined or empty variable
+        if let Some(default) = unquote_literal(part) {
+            if value.as_ref().is_none_or(|value| value.is_empty()) {
+                value = Some(default.to_string());
+            }
+            continue;
+        }
+
+        if let Some(current_value) = &value {
+            value = Some(apply_variable_filter(
+                path.clone(),
+                site,
+                part,
+                current_value,
+                current_depth,
+                context,
+            ));
+        }
+    }
+
+    match value {
+        Some(variable) => variable,
+        None => {
+            println!("The variable `{name}` is undefined, replacing with empty space.");
+            Default::default()
+        }
+    }
+}
+
+/**
+ * The amount of items of a folder embed like `identifier[..num]`
+ */
+fn parse_folder_count(component: &str, brackets: (usize, usize)) -> usize {
+    if brackets.1 - brackets.0 == 1 {
         usize::MAX
     } else if let Some(num_string) = &component[(brackets.0 + 1)..brackets.1]
         .to_string()
@@ -606,17 +1020,51 @@ pub fn parse_folder_embed(
             "The identifier `{component}` is not in the correct format. Use `identifier[..num]`"
         );
         usize::MAX
-    };
+    }
+}
+
+pub fn parse_folder_embed(
+    path: String,
+    site: &Site,
+    component: &str,
+    current_depth: u8,
+    brackets: (usize, usize),
+    context: &HashMap<String, String>,
+) -> String {
+    // A template turns the folder embed into a collection of pages
+    if let Some(template) = component[(brackets.1 + 1)..]
+        .trim()
+        .strip_prefix("as ")
+        .map(str::trim)
+    {
+        let items = get_collection(site, component[..brackets.0].trim());
+        let items = if component[(brackets.0 + 1)..brackets.1].trim() == "page" {
+            get_page_items(&path, items, context)
+        } else {
+            items
+                .into_iter()
+                .take(parse_folder_count(component, brackets))
+                .collect()
+        };
+        return render_collection(path, site, &items, template, current_depth, context);
+    }
+
+    let mut elem_count = parse_folder_count(component, brackets);
 
     // Collect the files that are being chained
-    let folder_embed_path = config.root.join("sections").join(&component[..brackets.0]);
+    let folder_embed_path = site
+        .config
+        .root
+        .join("sections")
+        .join(&component[..brackets.0]);
     match fs::read_dir(&folder_embed_path) {
         Ok(dirs) => {
             let mut collected_dirs: Vec<_> = dirs
                 .filter_map(|dir| match dir {
                     Ok(found_dir) => {
-                        let dir_path = found_dir.path();
-                        if dir_path.is_file() && FileType::has_valid_extension(&dir_path) {
+                        if FileType::from_path(&found_dir.path())
+                            .is_some_and(|filetype| filetype.is_file())
+                        {
                             Some(found_dir)
                         } else {
                             None
@@ -634,7 +1082,7 @@ pub fn parse_folder_embed(
                 let resolved_content = if let Some(resolved_content) = resolve_tokens_from_path(
                     path.clone(),
                     &section.path(),
-                    config,
+                    site,
                     current_depth,
                     context,
                 ) {
@@ -662,20 +1110,16 @@ pub fn parse_folder_embed(
 
 pub fn parse_single_embed(
     path: String,
-    config: &Configuration,
+    site: &Site,
     component: &str,
     current_depth: u8,
     context: &HashMap<String, String>,
 ) -> String {
-    let component_path = config.root.clone().join("sections").join(component);
-
-    if let Some(converted_content) = resolve_tokens_from_path(
-        path.clone(),
-        &component_path,
-        config,
-        current_depth,
-        context,
-    ) {
+    let component_path = site.config.root.clone().join("sections").join(component);
+
+    if let Some(converted_content) =
+        resolve_tokens_from_path(path.clone(), &component_path, site, current_depth, context)
+    {
         converted_content
     } else {
         println!("Wasn't able to read the file contents of `{path}`. returning empty component",);
@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Configuration,
+    dist::{
+        escape_html, find_source_file, get_page_href,
+        page::{get_page_title, is_date, read_front_matter},
+        site::Site,
+    },
+    filetype::FileType,
+};
+
+// The navigation of a folder within `<root>/pages`, like `_PAGES:blog`, is only built
+// when it's being used, from the pages collected once per dist. The current page is
+// marked right away.
+
+pub struct NavigationPage {
+    path: PathBuf,
+    href: String,
+    title: String,
+}
+
+fn is_index(page: &Path) -> bool {
+    page.file_stem().is_some_and(|stem| stem == "index")
+}
+
+fn build_navigation_link(href: &str, title: &str) -> String {
+    format!(
+        "<a href=\"{}\">{}</a>",
+        escape_html(href),
+        escape_html(title)
+    )
+}
+
+/**
+ * Builds the list of pages within `folder`. Subfolders become nested lists, that are
+ * labeled with the link to their index page, or just their name if there is none.
+ */
+fn build_navigation_level(pages: &[NavigationPage], folder: &Path, is_top_level: bool) -> String {
+    let mut items = String::default();
+    let mut subfolders: Vec<PathBuf> = Vec::new();
+
+    for page in pages {
+        let Ok(relative_path) = page.path.strip_prefix(folder) else {
+            continue;
+        };
+
+        let mut components = relative_path.components();
+        let Some(first_component) = components.next() else {
+            continue;
+        };
+
+        if components.next().is_none() {
+            // Index pages of subfolders are already the label of their list
+            if is_top_level || !is_index(&page.path) {
+                items.push_str(&format!(
+                    "<li>{}</li>",
+                    build_navigation_link(&page.href, &page.title)
+                ));
+            }
+            continue;
+        }
+
+        let subfolder = folder.join(first_component);
+        if subfolders.contains(&subfolder) {
+            continue;
+        }
+
+        let label = match pages
+            .iter()
+            .find(|page| page.path.parent() == Some(subfolder.as_path()) && is_index(&page.path))
+        {
+            Some(index) => build_navigation_link(&index.href, &index.title),
+            None => format!(
+                "<span>{}</span>",
+                escape_html(&first_component.as_os_str().to_string_lossy())
+            ),
+        };
+        let sublist = build_navigation_level(pages, &subfolder, false);
+        items.push_str(&format!("<li>{label}{sublist}</li>"));
+        subfolders.push(subfolder);
+    }
+
+    if is_top_level {
+        format!("<ul class=\"navigation\">{items}</ul>")
+    } else {
+        format!("<ul>{items}</ul>")
+    }
+}
+
+/**
+ * Collects the pages within `<root>/pages` with their links and titles, so the
+ * navigation of any folder can be built without reading them again.
+ */
+pub fn collect_navigation_pages(config: &Configuration, pages: &[PathBuf]) -> Vec<NavigationPage> {
+    let pages_path = config.root.join("pages");
+    pages
+        .iter()
+        .filter(|page| {
+            page.starts_with(&pages_path) && FileType::has_valid_extension(page) && page.is_file()
+        })
+        .map(|page| NavigationPage {
+            path: page.clone(),
+            href: get_page_href(config, page),
+            title: get_page_title(page),
+        })
+        .collect()
+}
+
+/**
+ * Marks the link to the current page within a navigation as active
+ */
+fn mark_active_page(navigation: &str, current_url: Option<&String>) -> String {
+    match current_url.map(|url| escape_html(url)) {
+        Some(url) => navigation.replace(
+            &format!("<li><a href=\"{url}\">"),
+            &format!("<li class=\"active\"><a href=\"{url}\" aria-current=\"page\">"),
+        ),
+        None => navigation.to_string(),
+    }
+}
+
+/**
+ * Builds the breadcrumbs of a page, linking to the index page of the website and every
+ * folder the page is in. Folders without an index page are shown by their name.
+ */
+pub fn build_breadcrumbs(config: &Configuration, page: &Path) -> String {
+    let pages_path = config.root.join("pages");
+    let mut items = String::default();
+
+    let home_page = config.root.join("index.html");
+    if page != home_page && home_page.is_file() {
+        items.push_str(&format!(
+            "<li>{}</li>",
+            build_navigation_link(
+                &get_page_href(config, &home_page),
+                &get_page_title(&home_page)
+            )
+        ));
+    }
+
+    if let Ok(relative_path) = page.parent().unwrap_or(page).strip_prefix(&pages_path) {
+        let mut folder = pages_path.clone();
+        let folders = std::iter::once(None).chain(relative_path.components().map(Some));
+        for component in folders {
+            if let Some(component) = component {
+                folder.push(component);
+            }
+
+            // The index page of the folder of the page is its own parent already
+            if is_index(page) && folder.as_path() == page.parent().unwrap_or(page) {
+                break;
+            }
+
+            match find_source_file(&folder.join("index")) {
+                Some((index, _)) => items.push_str(&format!(
+                    "<li>{}</li>",
+                    build_navigation_link(&get_page_href(config, &index), &get_page_title(&index))
+                )),
+                // The pages folder itself is no part of the website without an index
+                None if component.is_some() => items.push_str(&format!(
+                    "<li><span>{}</span></li>",
+                    escape_html(&folder.file_name().unwrap_or_default().to_string_lossy())
+                )),
+                None => (),
+            }
+        }
+    }
+
+    items.push_str(&format!(
+        "<li><span aria-current=\"page\">{}</span></li>",
+        escape_html(&get_page_title(page))
+    ));
+    format!("<ol class=\"breadcrumbs\">{items}</ol>")
+}
+
+/**
+ * The order of the pages in every folder within `<root>/pages`, for the links to the
+ * previous and next page. Index pages are left out. If all pages of a folder have a
+ * `date` in their front matter, they are sorted by it, oldest first. Otherwise they
+ * keep the order of `get_pages`.
+ */
+pub fn build_sibling_order(
+    config: &Configuration,
+    pages: &[PathBuf],
+) -> HashMap<PathBuf, Vec<PathBuf>> {
+    let pages_path = config.root.join("pages");
+    let mut folders: HashMap<PathBuf, Vec<(Option<String>, PathBuf)>> = HashMap::new();
+    for page in pages {
+        let Some(folder) = page
+            .parent()
+            .filter(|folder| folder.starts_with(&pages_path))
+        else {
+            continue;
+        };
+        if !(FileType::has_valid_extension(page) && page.is_file()) || is_index(page) {
+            continue;
+        }
+
+        let date = read_front_matter(page)
+            .get("date")
+            .filter(|date| is_date(date))
+            .cloned();
+        folders
+            .entry(folder.to_path_buf())
+            .or_default()
+            .push((date, page.clone()));
+    }
+
+    folders
+        .into_iter()
+        .map(|(folder, mut siblings)| {
+            if siblings.iter().all(|(date, _)| date.is_some()) {
+                siblings.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+            }
+            (folder, siblings.into_iter().map(|(_, page)| page).collect())
+        })
+        .collect()
+}
+
+/**
+ * The links to the previous and next page in the same folder, and their titles.
+ * They are empty, if there is no such page. Other than the variables above, they
+ * are part of the page context, so they can be used in conditions aswell.
+ */
+pub fn build_sibling_context(site: &Site, page: &Path) -> [(String, String); 4] {
+    let siblings = page
+        .parent()
+        .and_then(|folder| site.siblings.get(folder))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let position = siblings.iter().position(|sibling| sibling == page);
+    let previous = position
+        .and_then(|position| position.checked_sub(1))
+        .and_then(|position| siblings.get(position));
+    let next = position.and_then(|position| siblings.get(position + 1));
+
+    let link = |sibling: Option<&PathBuf>| {
+        sibling.map_or((String::default(), String::default()), |sibling| {
+            (get_page_href(site.config, sibling), get_page_title(sibling))
+        })
+    };
+    let (previous_url, previous_title) = link(previous);
+    let (next_url, next_title) = link(next);
+
+    [
+        ("_PREV_URL".to_string(), previous_url),
+        ("_PREV_TITLE".to_string(), previous_title),
+        ("_NEXT_URL".to_string(), next_url),
+        ("_NEXT_TITLE".to_string(), next_title),
+    ]
+}
+
+/**
+ * Resolves the navigation variables `_PAGES:folder` and `_BREADCRUMBS`. Returns
+ * `None`, if the name is none of them.
+ */
+pub fn resolve_navigation_variable(
+    site: &Site,
+    name: &str,
+    context: &HashMap<String, String>,
+) -> Option<String> {
+    if let Some(folder) = name.strip_prefix("_PAGES:") {
+        let folder = site
+            .config
+            .root
+            .join("pages")
+            .join(folder.trim().trim_matches('/'));
+        let navigation = build_navigation_level(&site.navigation_pages, &folder, true);
+        return Some(mark_active_page(&navigation, context.get("_URL")));
+    }
+
+    if name == "_BREADCRUMBS" {
+        let page = site.config.root.join(context.get("_PATH")?);
+        return Some(build_breadcrumbs(site.config, &page));
+    }
+
+    None
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::dist::{blocks::is_truthy, markdown::split_heading_id};
+
+/**
+ * Splits the front matter from the beginning of a page or section. The front matter
+ * is a block of `key: value` lines between two `---` lines, that has to be at the
+ * very start of the file. Returns the values and the remaining contents.
+ */
+pub fn split_front_matter(contents: &str) -> Option<(HashMap<String, String>, &str)> {
+    let mut lines = contents.split_inclusive('\n');
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    let mut front_matter = HashMap::new();
+    let mut offset = contents.find('\n')? + 1;
+    for line in lines {
+        offset += line.len();
+        let line = line.trim();
+
+        if line == "---" {
+            return Some((front_matter, &contents[offset..]));
+        }
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Anything else is most likely a horizontal line in markdown
+        let (key, value) = line.split_once(':')?;
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+            .unwrap_or(value);
+        front_matter.insert(key.trim().to_string(), value.to_string());
+    }
+
+    None
+}
+
+pub fn read_front_matter(path: &Path) -> HashMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| split_front_matter(&contents).map(|(front_matter, _)| front_matter))
+        .unwrap_or_default()
+}
+
+pub fn is_draft(front_matter: &HashMap<String, String>) -> bool {
+    front_matter
+        .get("draft")
+        .is_some_and(|draft| is_truthy(draft))
+}
+
+pub fn strip_html_tags(html: &str) -> String {
+    let mut text = String::default();
+    let mut in_tag = false;
+    for character in html.chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            character if !in_tag => text.push(character),
+            _ => (),
+        }
+    }
+    text.trim().to_string()
+}
+
+fn find_first_heading(contents: &str) -> Option<String> {
+    let markdown_heading = contents.lines().find_map(|line| {
+        let heading = line.trim_start().strip_prefix('#')?;
+        let heading = heading.trim_start_matches('#');
+        heading
+            .starts_with(char::is_whitespace)
+            .then(|| split_heading_id(heading).0.to_string())
+    });
+
+    let lowercase = contents.to_ascii_lowercase();
+    let html_heading = lowercase.find("<h1").and_then(|start| {
+        let content_start = start + lowercase[start..].find('>')? + 1;
+        let content_end = content_start + lowercase[content_start..].find("</h1")?;
+        Some(strip_html_tags(&contents[content_start..content_end]))
+    });
+
+    [markdown_heading, html_heading]
+        .into_iter()
+        .flatten()
+        .find(|heading| !heading.is_empty())
+}
+
+/**
+ * The title of a page. It's the `title` of the front matter, or the first heading
+ * of the page. If there is neither, the file name is used.
+ */
+pub fn get_page_title(path: &Path) -> String {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let (front_matter, contents) =
+        split_front_matter(&contents).unwrap_or((HashMap::default(), contents.as_str()));
+
+    front_matter
+        .get("title")
+        .cloned()
+        .or_else(|| find_first_heading(contents))
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        })
+}
+
+/**
+ * Checks if a value starts with a date like `2025-04-01`, like the `date` in the
+ * front matter. Anything after it, like the time of day, is allowed aswell.
+ */
+pub fn is_date(value: &str) -> bool {
+    value.len() >= 10
+        && value
+            .bytes()
+            .take(10)
+            .enumerate()
+            .all(|(index, byte)| match index {
+                4 | 7 => byte == b'-',
+                _ => byte.is_ascii_digit(),
+            })
+}
+
+/**
+ * The summary of a page. It's the `summary` of the front matter, or the first paragraph
+ * of the page, without any html tags.
+ */
+pub fn get_page_summary(path: &Path) -> String {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let (front_matter, contents) =
+        split_front_matter(&contents).unwrap_or((HashMap::default(), contents.as_str()));
+    if let Some(summary) = front_matter.get("summary") {
+        return summary.clone();
+    }
+
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        let is_text = !line.is_empty()
+            && !line.starts_with(['#', '<'])
+            && !line.starts_with("[##")
+            && !line.starts_with("---")
+            && !line.starts_with("```");
+        if is_text {
+            paragraph.push(line);
+        } else if !paragraph.is_empty() {
+            break;
+        }
+    }
+
+    // Html paragraphs are on their own line most of the time
+    if paragraph.is_empty() {
+        let lowercase = contents.to_ascii_lowercase();
+        if let Some(start) = ["<p>", "<p "]
+            .iter()
+            .filter_map(|tag| lowercase.find(tag))
+            .min()
+        {
+            let content_start = start + lowercase[start..].find('>').unwrap_or(0) + 1;
+            let content_end = lowercase[content_start..]
+                .find("</p")
+                .map_or(contents.len(), |end| content_start + end);
+            return strip_html_tags(&contents[content_start..content_end]);
+        }
+    }
+
+    strip_html_tags(&paragraph.join(" "))
+}
+
+/**
+ * The date of a page. It's the `date` of its front matter, or the time the file
+ * has been modified otherwise.
+ */
+pub fn get_page_date(page: &Path, front_matter: &HashMap<String, String>) -> String {
+    front_matter
+        .get("date")
+        .filter(|date| is_date(date))
+        .cloned()
+        .unwrap_or_else(|| {
+            fs::metadata(page)
+                .and_then(|metadata| metadata.modified())
+                .map(format_date)
+                .unwrap_or_default()
+        })
+}
+
+/**
+ * Formats a point in time as a date like `2025-04-01` (UTC)
+ */
+pub fn format_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+
+    // Converts the days since 1970-01-01 into a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = seconds.div_euclid(86400) + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
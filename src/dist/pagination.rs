@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Configuration,
+    dist::{
+        build_output_context,
+        collection::{CollectionItem, get_collection},
+        get_output_href, get_output_path,
+        page::read_front_matter,
+        render_page,
+        site::Site,
+        write_dist_file,
+    },
+};
+
+static DEFAULT_PER_PAGE: usize = 10;
+
+/**
+ * The path of a page of a paginated page within the dist directory. The first one
+ * is the page itself, the others are written to `page/<number>` next to an index
+ * page, or to `<name>-page/<number>` for any other page. A folder `<name>` would
+ * clash with the page itself, if the extension is hidden.
+ */
+pub fn get_pagination_path(config: &Configuration, page: &Path, number: usize) -> PathBuf {
+    let output_path = get_output_path(config, page);
+    if number <= 1 {
+        return output_path;
+    }
+
+    let mut pagination_path = output_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let file_stem = page.file_stem().unwrap_or_default();
+    if file_stem == "index" {
+        pagination_path.push("page");
+    } else {
+        let mut folder_name = file_stem.to_os_string();
+        folder_name.push("-page");
+        pagination_path.push(folder_name);
+    }
+    pagination_path.push(number.to_string());
+    if !config.hide_extension {
+        pagination_path.add_extension("html");
+    }
+    pagination_path
+}
+
+pub fn get_pagination_href(config: &Configuration, page: &Path, number: usize) -> String {
+    get_output_href(&get_pagination_path(config, page, number))
+}
+
+/**
+ * Writes a page, that declares `paginate: <folder>` in its front matter, once for
+ * every `per_page` pages of that collection.
+ */
+pub fn process_paginated_page(
+    site: &Site,
+    page: &Path,
+    collection: &str,
+    page_context: &HashMap<String, String>,
+) {
+    let per_page = read_front_matter(page)
+        .get("per_page")
+        .and_then(|per_page| per_page.parse::<usize>().ok())
+        .filter(|per_page| *per_page > 0)
+        .unwrap_or(DEFAULT_PER_PAGE);
+    let page_count = get_collection(site, collection)
+        .len()
+        .div_ceil(per_page)
+        .max(1);
+
+    for number in 1..=page_count {
+        if site.config.verbose {
+            println!(
+                "[verbose] {}: writing page {number} of {page_count}",
+                page.to_string_lossy()
+            );
+        }
+
+        // The link and the way back to the root differ for every page
+        let pagination_path = get_pagination_path(site.config, page, number);
+        let mut context = page_context.clone();
+        context.extend(build_output_context(&pagination_path));
+        context.extend([
+            ("_PAGE_NUMBER".to_string(), number.to_string()),
+            ("_PAGE_COUNT".to_string(), page_count.to_string()),
+            ("_PER_PAGE".to_string(), per_page.to_string()),
+            (
+                "_PAGE_PREV_URL".to_string(),
+                if number > 1 {
+                    get_pagination_href(site.config, page, number - 1)
+                } else {
+                    String::default()
+                },
+            ),
+            (
+                "_PAGE_NEXT_URL".to_string(),
+                if number < page_count {
+                    get_pagination_href(site.config, page, number + 1)
+                } else {
+                    String::default()
+                },
+            ),
+        ]);
+
+        let contents = render_page(site, page, &context);
+        write_dist_file(site.config, &pagination_path, contents);
+    }
+}
+
+/**
+ * The items of a collection, that belong on the current page of a paginated page,
+ * like in `<## posts[page] as post_card>`.
+ */
+pub fn get_page_items(
+    path: &str,
+    items: Vec<CollectionItem>,
+    context: &HashMap<String, String>,
+) -> Vec<CollectionItem> {
+    let page_number = context
+        .get("_PAGE_NUMBER")
+        .and_then(|number| number.parse::<usize>().ok());
+    let per_page = context
+        .get("_PER_PAGE")
+        .and_then(|per_page| per_page.parse::<usize>().ok());
+
+    match (page_number, per_page) {
+        (Some(page_number), Some(per_page)) => items
+            .into_iter()
+            .skip((page_number.max(1) - 1) * per_page)
+            .take(per_page)
+            .collect(),
+        _ => {
+            println!(
+                "{path}: `[page]` is only available on pages with `paginate` in their front matter, including all items"
+            );
+            items
+        }
+    }
+}
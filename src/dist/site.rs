@@ -0,0 +1,46 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{
+    Configuration,
+    dist::{
+        get_folder_pages,
+        navigation::{NavigationPage, build_sibling_order, collect_navigation_pages},
+    },
+    filetype::FileType,
+};
+
+// Everything, that is collected once per dist and shared by all the pages being built.
+// Other than the default context, it's no part of the variables of a page.
+
+pub struct Site<'a> {
+    pub config: &'a Configuration,
+    /** All the pages within `<root>/pages`, for collections */
+    pub folder_pages: Vec<PathBuf>,
+    /** The pages within `<root>/pages` being built, with their links and titles */
+    pub navigation_pages: Vec<NavigationPage>,
+    /** The pages being built within every folder, in the order they link to each other */
+    pub siblings: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl<'a> Site<'a> {
+    pub fn new(config: &'a Configuration, input_pages: &[PathBuf]) -> Site<'a> {
+        Site {
+            config,
+            folder_pages: get_folder_pages(config),
+            navigation_pages: collect_navigation_pages(config, input_pages),
+            siblings: build_sibling_order(config, input_pages),
+        }
+    }
+
+    /**
+     * All the pages within `<root>/pages/<folder>`, whether they are being built or not
+     */
+    pub fn get_pages_in_folder(&self, folder: &str) -> Vec<PathBuf> {
+        let folder_path = self.config.root.join("pages").join(folder);
+        self.folder_pages
+            .iter()
+            .filter(|page| page.starts_with(&folder_path) && FileType::has_valid_extension(page))
+            .cloned()
+            .collect()
+    }
+}
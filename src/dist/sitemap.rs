@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    Configuration,
+    dist::{
+        escape_html,
+        feed::to_atom_date,
+        get_page_href,
+        page::{get_page_date, is_date, read_front_matter},
+        write_dist_file,
+    },
+    filetype::FileType,
+};
+
+/**
+ * The date a page has been changed last. It's the `updated` of the front matter,
+ * and the date of the page otherwise. Dates with a time are completed to a full
+ * W3C datetime like in the feed, since sitemaps don't allow anything in between.
+ */
+pub fn get_last_modified(page: &Path) -> String {
+    let front_matter = read_front_matter(page);
+    let date = front_matter
+        .get("updated")
+        .filter(|updated| is_date(updated))
+        .cloned()
+        .unwrap_or_else(|| get_page_date(page, &front_matter));
+
+    if date.len() > 10 {
+        to_atom_date(&date)
+    } else {
+        date
+    }
+}
+
+pub fn build_sitemap(config: &Configuration, base_url: &str, pages: &[PathBuf]) -> String {
+    let mut sitemap = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+
+    for page in pages {
+        if !(FileType::has_valid_extension(page) && page.is_file()) {
+            continue;
+        }
+        sitemap.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+            escape_html(&format!("{base_url}{}", get_page_href(config, page))),
+            get_last_modified(page)
+        ));
+    }
+
+    sitemap.push_str("</urlset>\n");
+    sitemap
+}
+
+/**
+ * Writes the sitemap.xml with all the pages into the dist directory. The links in
+ * a sitemap have to be absolute, so it's skipped if there is no base url.
+ */
+pub fn write_sitemap(config: &Configuration, pages: &[PathBuf]) {
+    let Some(base_url) = &config.base_url else {
+        println!("There is no base_url configured, skipping the sitemap.xml");
+        return;
+    };
+
+    println!("Writing sitemap.xml ...");
+    write_dist_file(
+        config,
+        Path::new("sitemap.xml"),
+        build_sitemap(config, base_url, pages),
+    );
+}
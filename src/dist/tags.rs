@@ -0,0 +1,236 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Configuration,
+    dist::{
+        apply_layouts, build_output_context, escape_html, find_source_file, get_page_href,
+        page::{get_page_date, get_page_title, read_front_matter},
+        resolve_tokens_from_path,
+        site::Site,
+        slugify, write_dist_file,
+    },
+    filetype::FileType,
+};
+
+/**
+ * The tags of a page, from `tags: a, b` or `tags: [a, b]` in its front matter
+ */
+pub fn get_page_tags(front_matter: &HashMap<String, String>) -> Vec<String> {
+    let Some(tags) = front_matter.get("tags") else {
+        return Vec::default();
+    };
+    let tags = tags.trim();
+    let tags = tags
+        .strip_prefix('[')
+        .and_then(|tags| tags.strip_suffix(']'))
+        .unwrap_or(tags);
+    tags.split(',')
+        .map(|tag| tag.trim().trim_matches(['"', '\'']).trim())
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/**
+ * The name of the page of a tag. Symbols that tell tags apart are spelled out, so
+ * `C`, `C#` and `C++` become `c`, `c-sharp` and `c-plus-plus`.
+ */
+pub fn get_tag_slug(tag: &str) -> String {
+    let mut spelled_out = String::default();
+    for character in tag.chars() {
+        match character {
+            '#' => spelled_out.push_str(" sharp "),
+            '+' => spelled_out.push_str(" plus "),
+            '&' => spelled_out.push_str(" and "),
+            '@' => spelled_out.push_str(" at "),
+            '.' => spelled_out.push_str(" dot "),
+            character => spelled_out.push(character),
+        }
+    }
+
+    let slug = slugify(&spelled_out);
+    if slug.is_empty() {
+        "tag".to_string()
+    } else {
+        slug
+    }
+}
+
+/**
+ * The path of the page listing all pages with `tag` within the dist directory
+ */
+pub fn get_tag_path(config: &Configuration, tag: &str) -> PathBuf {
+    let mut tag_path = Path::new("tags").join(get_tag_slug(tag));
+    if !config.hide_extension {
+        tag_path.add_extension("html");
+    }
+    tag_path
+}
+
+pub fn get_tag_href(config: &Configuration, tag: &str) -> String {
+    format!("/{}", get_tag_path(config, tag).to_string_lossy())
+}
+
+/**
+ * Builds an unordered list of links to the pages of the tags. It's empty without
+ * any tags, so it can be used in conditions.
+ */
+pub fn build_tags_list(config: &Configuration, tags: &[String]) -> String {
+    if tags.is_empty() {
+        return String::default();
+    }
+
+    let mut tags_string = String::from("<ul class=\"tags\">");
+    for tag in tags {
+        tags_string.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>",
+            get_tag_href(config, tag),
+            escape_html(tag)
+        ));
+    }
+    tags_string.push_str("</ul>");
+    tags_string
+}
+
+/**
+ * Collects the pages of every tag, sorted by the name of the tag. The pages
+ * of a tag are sorted by date, newest first. Tags sharing the same page, like
+ * `Rust` and `rust`, are listed together under the first name.
+ */
+pub fn collect_tags(pages: &[PathBuf]) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut tags: BTreeMap<String, Vec<(String, PathBuf)>> = BTreeMap::new();
+    for page in pages {
+        if !(FileType::has_valid_extension(page) && page.is_file()) {
+            continue;
+        }
+        let front_matter = read_front_matter(page);
+        for tag in get_page_tags(&front_matter) {
+            tags.entry(tag)
+                .or_default()
+                .push((get_page_date(page, &front_matter), page.clone()));
+        }
+    }
+
+    let mut slugs: HashMap<String, String> = HashMap::new();
+    let mut merged_tags: BTreeMap<String, Vec<(String, PathBuf)>> = BTreeMap::new();
+    for (tag, tag_pages) in tags {
+        let slug = get_tag_slug(&tag);
+        match slugs.get(&slug) {
+            Some(first_tag) => {
+                println!(
+                    "The tags `{first_tag}` and `{tag}` share the page `{slug}`, listing them together"
+                );
+                merged_tags
+                    .entry(first_tag.clone())
+                    .or_default()
+                    .extend(tag_pages);
+            }
+            None => {
+                slugs.insert(slug, tag.clone());
+                merged_tags.insert(tag, tag_pages);
+            }
+        }
+    }
+
+    merged_tags
+        .into_iter()
+        .map(|(tag, mut tag_pages)| {
+            tag_pages.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+            tag_pages.dedup_by(|a, b| a.1 == b.1);
+            (tag, tag_pages.into_iter().map(|(_, page)| page).collect())
+        })
+        .collect()
+}
+
+/**
+ * Renders a tag layout into `output_path`, with the link and the prefix leading back
+ * to the root of that page. Tag pages have no source of their own, so their `_PATH`
+ * is the path within the dist directory.
+ */
+fn render_tag_layout(
+    site: &Site,
+    layout: &Path,
+    output_path: &Path,
+    context: &HashMap<String, String>,
+) {
+    let mut context = context.clone();
+    context.extend(build_output_context(output_path));
+    context.insert(
+        "_PATH".to_string(),
+        output_path.to_string_lossy().to_string(),
+    );
+
+    let contents = resolve_tokens_from_path(
+        output_path.to_string_lossy().to_string(),
+        layout,
+        site,
+        0,
+        &context,
+    )
+    .unwrap_or_default();
+    let contents = apply_layouts(site, layout, contents, &context);
+    write_dist_file(site.config, output_path, contents);
+}
+
+/**
+ * Writes a page for every tag using the layout `tag`, and an overview of all tags
+ * using the layout `tags`. Each layout is optional.
+ */
+pub fn write_tag_pages(site: &Site, pages: &[PathBuf], default_context: &HashMap<String, String>) {
+    let tags = collect_tags(pages);
+    if tags.is_empty() {
+        return;
+    }
+
+    let layouts_path = site.config.root.join("layouts");
+    match find_source_file(&layouts_path.join("tag")) {
+        Some((layout, _)) => {
+            for (tag, tag_pages) in &tags {
+                let output_path = get_tag_path(site.config, tag);
+                println!("Writing {} ...", output_path.to_string_lossy());
+
+                let mut pages_string = String::from("<ul class=\"tag-pages\">");
+                for page in tag_pages {
+                    pages_string.push_str(&format!(
+                        "<li><a href=\"{}\">{}</a></li>",
+                        get_page_href(site.config, page),
+                        escape_html(&get_page_title(page))
+                    ));
+                }
+                pages_string.push_str("</ul>");
+
+                let mut context = default_context.clone();
+                context.extend([
+                    ("_TAG".to_string(), escape_html(tag)),
+                    ("_TAG_URL".to_string(), get_tag_href(site.config, tag)),
+                    ("_TAG_PAGES".to_string(), pages_string),
+                    ("_TITLE".to_string(), escape_html(tag)),
+                ]);
+                render_tag_layout(site, &layout, &output_path, &context);
+            }
+        }
+        None => println!(
+            "There is no tag layout in `{}`, skipping the pages of the tags",
+            layouts_path.to_string_lossy()
+        ),
+    }
+
+    if let Some((layout, _)) = find_source_file(&layouts_path.join("tags")) {
+        let output_path = Path::new("tags").join("index.html");
+        println!("Writing {} ...", output_path.to_string_lossy());
+
+        let tag_names: Vec<String> = tags.keys().cloned().collect();
+        let mut context = default_context.clone();
+        context.extend([
+            (
+                "_TAGS".to_string(),
+                build_tags_list(site.config, &tag_names),
+            ),
+            ("_TITLE".to_string(), "Tags".to_string()),
+        ]);
+        render_tag_layout(site, &layout, &output_path, &context);
+    }
+}
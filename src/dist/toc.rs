@@ -0,0 +1,201 @@
+use crate::dist::{page::strip_html_tags, slugify};
+
+// The headings of a page are only known after it has been rendered. So `_TOC` is
+// a placeholder while rendering, that's replaced with the table of contents after.
+pub static TOC_PLACEHOLDER: &str = "<!--static_atoms:toc-->";
+
+pub struct Heading {
+    pub level: usize,
+    pub id: String,
+    pub text: String,
+}
+
+/**
+ * Returns `id`, or `id` with a number appended if it's already used
+ */
+fn make_unique_id(id: &str, used_ids: &mut Vec<String>) -> String {
+    let mut unique_id = id.to_string();
+    let mut number = 1;
+    while used_ids.contains(&unique_id) {
+        number += 1;
+        unique_id = format!("{id}-{number}");
+    }
+    used_ids.push(unique_id.clone());
+    unique_id
+}
+
+/**
+ * Returns a unique id based on `text`, by appending a number if it's already used
+ */
+pub fn get_unique_id(text: &str, used_ids: &mut Vec<String>) -> String {
+    let slug = slugify(text);
+    if slug.is_empty() {
+        make_unique_id("section", used_ids)
+    } else {
+        make_unique_id(&slug, used_ids)
+    }
+}
+
+/**
+ * Removes html entities like `&amp;`, so they don't end up in ids
+ */
+pub fn strip_html_entities(text: &str) -> String {
+    let mut stripped = String::default();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        stripped.push_str(&rest[..start]);
+        rest = match rest[start..].find(';') {
+            Some(end)
+                if rest[(start + 1)..(start + end)]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '#') =>
+            {
+                &rest[(start + end + 1)..]
+            }
+            _ => {
+                stripped.push('&');
+                &rest[(start + 1)..]
+            }
+        };
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+fn find_heading_start(lowercase: &str, offset: usize) -> Option<(usize, usize)> {
+    let mut offset = offset;
+    while let Some(found) = lowercase[offset..].find("<h") {
+        let start = offset + found;
+        let bytes = lowercase.as_bytes();
+        if let (Some(level @ b'1'..=b'6'), Some(b'>' | b' ' | b'\t' | b'\n' | b'\r')) =
+            (bytes.get(start + 2), bytes.get(start + 3))
+        {
+            return Some((start, (level - b'0') as usize));
+        }
+        offset = start + 2;
+    }
+    None
+}
+
+/**
+ * The start and end of the value of the id attribute within a tag
+ */
+fn find_id_attribute(tag: &str) -> Option<(usize, usize)> {
+    let lowercase = tag.to_ascii_lowercase();
+    let start = [" id=\"", "\tid=\"", "\nid=\""]
+        .iter()
+        .filter_map(|attribute| lowercase.find(attribute))
+        .min()?
+        + 5;
+    let end = start + tag[start..].find('"')?;
+    Some((start, end))
+}
+
+/**
+ * Gives every html heading of a rendered page an id, unless it already has one.
+ * Ids used twice, like from two sections with the same heading, are numbered.
+ * Returns the page with the ids, and all of its headings in order.
+ */
+pub fn add_heading_ids(html: &str) -> (String, Vec<Heading>) {
+    let lowercase = html.to_ascii_lowercase();
+    let mut with_ids = String::with_capacity(html.len());
+    let mut headings = Vec::new();
+    let mut used_ids: Vec<String> = Vec::new();
+
+    let mut offset = 0usize;
+    while let Some((start, level)) = find_heading_start(&lowercase, offset) {
+        let Some(tag_end) = lowercase[start..].find('>').map(|end| start + end) else {
+            break;
+        };
+        let Some(content_end) = lowercase[tag_end..]
+            .find(&format!("</h{level}"))
+            .map(|end| tag_end + end)
+        else {
+            break;
+        };
+
+        let content = &html[(tag_end + 1)..content_end];
+        let permalink_start = content.find("<a class=\"permalink\"");
+        let text =
+            strip_html_tags(permalink_start.map_or(content, |permalink| &content[..permalink]));
+        let id = match find_id_attribute(&html[start..tag_end]) {
+            Some((id_start, id_end)) => {
+                let id = &html[(start + id_start)..(start + id_end)];
+                let unique_id = make_unique_id(id, &mut used_ids);
+                with_ids.push_str(&html[offset..(start + id_start)]);
+                with_ids.push_str(&unique_id);
+                with_ids.push_str(&html[(start + id_end)..tag_end]);
+                if unique_id != id
+                    && let Some(permalink_start) = permalink_start
+                {
+                    // The permalink of the heading has to point to the new id aswell
+                    with_ids.push_str(&html[tag_end..(tag_end + 1 + permalink_start)]);
+                    with_ids.push_str(&content[permalink_start..].replacen(
+                        &format!("href=\"#{id}\""),
+                        &format!("href=\"#{unique_id}\""),
+                        1,
+                    ));
+                    offset = content_end;
+                } else {
+                    offset = tag_end;
+                }
+                unique_id
+            }
+            None => {
+                let id = get_unique_id(&strip_html_entities(&text), &mut used_ids);
+                with_ids.push_str(&html[offset..tag_end]);
+                with_ids.push_str(&format!(" id=\"{id}\""));
+                offset = tag_end;
+                id
+            }
+        };
+        headings.push(Heading { level, id, text });
+    }
+    with_ids.push_str(&html[offset..]);
+
+    (with_ids, headings)
+}
+
+/**
+ * Builds the table of contents as nested lists of links to the headings. It's
+ * empty for pages without headings.
+ */
+pub fn build_toc(headings: &[Heading]) -> String {
+    let mut toc = String::default();
+    let mut open_levels: Vec<usize> = Vec::new();
+
+    for heading in headings {
+        match open_levels.last() {
+            None => {
+                toc.push_str("<ul class=\"toc\"><li>");
+                open_levels.push(heading.level);
+            }
+            Some(_) => {
+                while open_levels.len() > 1
+                    && open_levels
+                        .last()
+                        .is_some_and(|level| *level > heading.level)
+                {
+                    toc.push_str("</li></ul>");
+                    open_levels.pop();
+                }
+
+                if open_levels
+                    .last()
+                    .is_some_and(|level| heading.level > *level)
+                {
+                    toc.push_str("<ul><li>");
+                    open_levels.push(heading.level);
+                } else {
+                    toc.push_str("</li><li>");
+                }
+            }
+        }
+        toc.push_str(&format!("<a href=\"#{}\">{}</a>", heading.id, heading.text));
+    }
+
+    for _ in open_levels {
+        toc.push_str("</li></ul>");
+    }
+    toc
+}
@@ -1,9 +1,6 @@
-use std::{collections::HashMap, ffi::OsStr, path::PathBuf};
+use std::{collections::HashMap, ffi::OsStr, path::Path};
 
-use crate::{
-    Configuration,
-    dist::{markdown::resolve_tokens_markdown, resolve_tokens_html},
-};
+use crate::dist::{markdown::resolve_tokens_markdown, resolve_tokens_html, site::Site};
 
 static FILE_TYPES: [FileType; 3] = [
     FileType::FileHTML,
@@ -34,13 +31,26 @@ impl FileType {
     }
 
     pub fn is_file(&self) -> bool {
-        match self {
-            FileType::Directory => false,
-            _ => true,
+        !matches!(self, FileType::Directory)
+    }
+
+    /**
+     * The type of the file or folder at `path`, if it's a folder or a file that
+     * can be transformed
+     */
+    pub fn from_path(path: &Path) -> Option<FileType> {
+        if path.is_dir() {
+            return Some(FileType::Directory);
         }
+
+        let extension = path.extension().and_then(OsStr::to_str)?;
+        FILE_TYPES
+            .iter()
+            .find(|filetype| path.is_file() && filetype.extension() == extension)
+            .cloned()
     }
 
-    pub fn has_valid_extension(path: &PathBuf) -> bool {
+    pub fn has_valid_extension(path: &Path) -> bool {
         match path.extension().and_then(OsStr::to_str) {
             Some(file_ext) => {
                 for filetype in &FILE_TYPES {
@@ -62,7 +72,7 @@ impl FileType {
         &self,
         path_string: String,
         content: &str,
-        config: &Configuration,
+        site: &Site,
         depth: u8,
         context: &HashMap<String, String>,
     ) -> String {
@@ -70,10 +80,10 @@ impl FileType {
             FileType::Directory => {
                 panic!("Converting the content from a folder format does not make any sense!")
             }
-            FileType::FileHTML => resolve_tokens_html(path_string, config, content, depth, context),
+            FileType::FileHTML => resolve_tokens_html(path_string, site, content, depth, context),
             FileType::FileMarkdown => resolve_tokens_markdown(
                 path_string,
-                config,
+                site,
                 content,
                 depth,
                 context,
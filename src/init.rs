@@ -0,0 +1,133 @@
+use std::fs;
+
+use crate::{Configuration, config_file::CONFIG_FILE_NAME};
+
+static PROJECT_FOLDERS: [&str; 4] = ["pages", "sections", "media", "root"];
+
+static INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>My static atoms website</title>
+    <link rel="stylesheet" href="/style.css">
+  </head>
+  <body>
+    <## header(title="Welcome!" subtitle="This website is built with static atoms")>
+    <main>
+      <h2>News</h2>
+      <## news[]>
+      <h2>All pages</h2>
+      <## {_PAGES}>
+    </main>
+    <footer>Built with <## {_APPLINK}> <## {_VERSION}></footer>
+  </body>
+</html>
+"#;
+
+static HEADER_SECTION: &str = r#"<header>
+  <h1><## {title}></h1>
+  <p><## {subtitle}></p>
+</header>
+"#;
+
+static NEWS_FIRST_SECTION: &str = r#"<article>
+  <h3>Hello world</h3>
+  <p>Every file in <code>sections/news</code> is included by the folder embed in the index.html.</p>
+</article>
+"#;
+
+static NEWS_SECOND_SECTION: &str = r#"### Written in markdown
+
+Sections can also be written in **markdown**. They are included in alphabetic order.
+"#;
+
+static ABOUT_PAGE: &str = r#"# About
+
+This page lives in `pages/about.md` and is transformed into `dist/pages/about.html`.
+Sections can be embedded into markdown pages, too:
+
+[## header(title="About this website" subtitle="Embedded from sections/header.html")]
+"#;
+
+static STYLE_CSS: &str = r#"body {
+  font-family: sans-serif;
+  max-width: 48rem;
+  margin: 0 auto;
+  padding: 1rem;
+}
+"#;
+
+static CONFIG_FILE: &str = r#"# Project configuration for static_atoms. Parameters on the command line
+# always override the values in here. Paths are relative to the project root.
+
+# out = "dist"
+# in = ["pages", "index.html"]
+# depth = 8
+# base_url = "https://example.com"
+# hide_extension = false
+# clean = false
+# verbose = false
+# permalinks = false
+
+# [feed]
+# folder = "pages/blog"
+# title = "My blog"
+"#;
+
+static PROJECT_FILES: [(&str, &str); 7] = [
+    ("index.html", INDEX_HTML),
+    ("sections/header.html", HEADER_SECTION),
+    ("sections/news/01_hello.html", NEWS_FIRST_SECTION),
+    ("sections/news/02_markdown.md", NEWS_SECOND_SECTION),
+    ("pages/about.md", ABOUT_PAGE),
+    ("root/style.css", STYLE_CSS),
+    (CONFIG_FILE_NAME, CONFIG_FILE),
+];
+
+/**
+ * Creates the directory layout of a new project including a few example files.
+ * Files, that already exist are never overwritten.
+ */
+pub fn run_init(config: &Configuration) {
+    println!(
+        "Creating a new project in {} ...",
+        config.root.to_string_lossy()
+    );
+
+    for folder in PROJECT_FOLDERS {
+        let folder_path = config.root.join(folder);
+        if config.write && fs::create_dir_all(&folder_path).is_err() {
+            println!(
+                "Wasn't able to create directory `{}`",
+                folder_path.to_string_lossy()
+            );
+        }
+    }
+
+    for (file, content) in PROJECT_FILES {
+        let file_path = config.root.join(file);
+        if file_path.exists() {
+            println!("Skipping {file}, since it already exists");
+            continue;
+        }
+
+        if config.write {
+            if let Some(parent) = file_path.parent() {
+                _ = fs::create_dir_all(parent);
+            }
+            if fs::write(&file_path, content).is_err() {
+                println!(
+                    "Wasn't able to write file to path `{}`, ignoring",
+                    file_path.to_string_lossy()
+                );
+                continue;
+            }
+            println!("Created {file}");
+        } else {
+            println!("Would create {file}");
+        }
+    }
+
+    println!("Done! Run `static_atoms dist` or `static_atoms serve` to build the website.");
+}
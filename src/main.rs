@@ -1,13 +1,25 @@
+mod config_file;
 mod dist;
 mod filetype;
+mod init;
+mod serve;
 #[cfg(test)]
 mod tests;
+mod watch;
 
-use std::{env, path::PathBuf};
+use std::{collections::HashMap, env, path::PathBuf};
 
-use crate::dist::run_dist;
+use crate::{
+    config_file::{CONFIG_FILE_NAME, load_config_file},
+    dist::run_dist,
+    init::run_init,
+    serve::run_serve,
+    watch::run_watch,
+};
 
 static DEFAULT_MAX_DEPTH: u8 = 8;
+static DEFAULT_WATCH_INTERVAL: u64 = 500;
+static DEFAULT_PORT: u16 = 8080;
 
 pub struct Configuration {
     root: PathBuf,
@@ -18,6 +30,43 @@ pub struct Configuration {
     write: bool,
     verbose: bool,
     max_depth: u8,
+    watch_interval: u64,
+    port: u16,
+    base_url: Option<String>,
+    feed: Option<PathBuf>,
+    feed_title: Option<String>,
+    permalinks: bool,
+    sources: HashMap<&'static str, ConfigSource>,
+}
+
+/**
+ * Where a value of the configuration came from. Parameters on the command line
+ * always take precedence over the values in the project configuration file.
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConfigSource {
+    Default,
+    File,
+    Cli,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File => write!(f, "{CONFIG_FILE_NAME}"),
+            ConfigSource::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+impl Configuration {
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources
+            .get(key)
+            .copied()
+            .unwrap_or(ConfigSource::Default)
+    }
 }
 
 impl std::fmt::Display for Configuration {
@@ -26,28 +75,74 @@ impl std::fmt::Display for Configuration {
             f,
             "{}",
             [
-                format!("  root: `{}`", self.root.to_string_lossy()),
                 format!(
-                    "  out: `{}`",
+                    "  root: `{}` ({})",
+                    self.root.to_string_lossy(),
+                    self.source("root")
+                ),
+                format!(
+                    "  out: `{}` ({})",
                     match &self.out {
                         Some(out) => out.to_string_lossy().to_string(),
                         None => "<root>/dist".to_owned(),
-                    }
+                    },
+                    self.source("out")
                 ),
-                format!("  clean: `{}`", self.clean),
-                format!("  write: `{}`", self.write),
-                format!("  max_depth: `{}`", self.max_depth),
-                format!("  hide_extension: `{}`", self.hide_extension),
-                format!("  verbose: `{}`", self.verbose),
-                format!("  input_files: `{}`", {
-                    let mut array_string: String = "[".into();
-
-                    for path in &self.input_files {
-                        array_string = array_string + path.to_str().unwrap_or("") + ", ";
-                    }
+                format!("  clean: `{}` ({})", self.clean, self.source("clean")),
+                format!("  write: `{}` ({})", self.write, self.source("write")),
+                format!(
+                    "  max_depth: `{}` ({})",
+                    self.max_depth,
+                    self.source("depth")
+                ),
+                format!(
+                    "  hide_extension: `{}` ({})",
+                    self.hide_extension,
+                    self.source("hide_extension")
+                ),
+                format!("  verbose: `{}` ({})", self.verbose, self.source("verbose")),
+                format!(
+                    "  permalinks: `{}` ({})",
+                    self.permalinks,
+                    self.source("permalinks")
+                ),
+                format!(
+                    "  watch_interval: `{}ms` ({})",
+                    self.watch_interval,
+                    self.source("interval")
+                ),
+                format!("  port: `{}` ({})", self.port, self.source("port")),
+                format!(
+                    "  base_url: `{}` ({})",
+                    self.base_url.as_deref().unwrap_or("<none>"),
+                    self.source("base_url")
+                ),
+                format!(
+                    "  feed: `{}` ({})",
+                    match &self.feed {
+                        Some(feed) => feed.to_string_lossy().to_string(),
+                        None => "<none>".to_owned(),
+                    },
+                    self.source("feed.folder")
+                ),
+                format!(
+                    "  feed_title: `{}` ({})",
+                    self.feed_title.as_deref().unwrap_or("<folder name>"),
+                    self.source("feed.title")
+                ),
+                format!(
+                    "  input_files: `{}` ({})",
+                    {
+                        let mut array_string: String = "[".into();
 
-                    array_string + "]"
-                }),
+                        for path in &self.input_files {
+                            array_string = array_string + path.to_str().unwrap_or("") + ", ";
+                        }
+
+                        array_string + "]"
+                    },
+                    self.source("in")
+                ),
             ]
             .join("\n")
         )
@@ -57,6 +152,9 @@ impl std::fmt::Display for Configuration {
 pub enum Action {
     RunHelp,
     RunDist,
+    RunWatch,
+    RunServe,
+    RunInit,
     ParamsHelp,
     ShowConfig,
 }
@@ -74,6 +172,13 @@ fn main() {
         verbose: false,
         max_depth: DEFAULT_MAX_DEPTH,
         hide_extension: false,
+        watch_interval: DEFAULT_WATCH_INTERVAL,
+        port: DEFAULT_PORT,
+        base_url: None,
+        feed: None,
+        feed_title: None,
+        permalinks: false,
+        sources: HashMap::default(),
     };
     let mut action = Action::RunHelp;
 
@@ -87,39 +192,52 @@ fn main() {
         if let Some(param) = arg.strip_prefix("--") {
             if param.eq_ignore_ascii_case("clean") {
                 config.clean = true;
+                config.sources.insert("clean", ConfigSource::Cli);
                 continue;
             }
 
             if param.eq_ignore_ascii_case("dry") {
                 config.write = false;
+                config.sources.insert("write", ConfigSource::Cli);
                 continue;
             }
 
             if param.eq_ignore_ascii_case("hide-extension") {
                 config.hide_extension = true;
+                config.sources.insert("hide_extension", ConfigSource::Cli);
                 continue;
             }
 
             if param.eq_ignore_ascii_case("verbose") {
                 config.verbose = true;
+                config.sources.insert("verbose", ConfigSource::Cli);
+                continue;
+            }
+
+            if param.eq_ignore_ascii_case("permalinks") {
+                config.permalinks = true;
+                config.sources.insert("permalinks", ConfigSource::Cli);
                 continue;
             }
 
             if let Some(out_param) = param.strip_prefix("out=") {
                 let path: PathBuf = PathBuf::from(out_param);
                 config.out = Some(path);
+                config.sources.insert("out", ConfigSource::Cli);
                 continue;
             }
 
             if let Some(root_param) = param.strip_prefix("root=") {
                 let path: PathBuf = PathBuf::from(root_param);
                 config.root = path;
+                config.sources.insert("root", ConfigSource::Cli);
                 continue;
             }
 
             if let Some(input_param) = param.strip_prefix("in=") {
                 let path: PathBuf = PathBuf::from(input_param);
                 config.input_files.push(path);
+                config.sources.insert("in", ConfigSource::Cli);
                 continue;
             }
 
@@ -128,6 +246,37 @@ fn main() {
                     .parse()
                     .expect("The depth parameter does not contain a valid number");
                 config.max_depth = depth;
+                config.sources.insert("depth", ConfigSource::Cli);
+                continue;
+            }
+
+            if let Some(interval_param) = param.strip_prefix("interval=") {
+                let interval: u64 = interval_param
+                    .parse()
+                    .expect("The interval parameter does not contain a valid number");
+                config.watch_interval = interval;
+                config.sources.insert("interval", ConfigSource::Cli);
+                continue;
+            }
+
+            if let Some(port_param) = param.strip_prefix("port=") {
+                let port: u16 = port_param
+                    .parse()
+                    .expect("The port parameter does not contain a valid port number");
+                config.port = port;
+                config.sources.insert("port", ConfigSource::Cli);
+                continue;
+            }
+
+            if let Some(base_url_param) = param.strip_prefix("base-url=") {
+                config.base_url = Some(base_url_param.trim_end_matches('/').to_string());
+                config.sources.insert("base_url", ConfigSource::Cli);
+                continue;
+            }
+
+            if let Some(feed_param) = param.strip_prefix("feed=") {
+                config.feed = Some(PathBuf::from(feed_param));
+                config.sources.insert("feed.folder", ConfigSource::Cli);
                 continue;
             }
         }
@@ -139,21 +288,25 @@ fn main() {
                 match letter {
                     'c' => {
                         config.clean = true;
+                        config.sources.insert("clean", ConfigSource::Cli);
                         continue;
                     }
 
                     'd' => {
                         config.write = false;
+                        config.sources.insert("write", ConfigSource::Cli);
                         continue;
                     }
 
                     'h' => {
                         config.hide_extension = true;
+                        config.sources.insert("hide_extension", ConfigSource::Cli);
                         continue;
                     }
 
                     'v' => {
                         config.verbose = true;
+                        config.sources.insert("verbose", ConfigSource::Cli);
                         continue;
                     }
 
@@ -175,6 +328,21 @@ fn main() {
             continue;
         }
 
+        if arg.eq_ignore_ascii_case("watch") {
+            action = Action::RunWatch;
+            continue;
+        }
+
+        if arg.eq_ignore_ascii_case("serve") {
+            action = Action::RunServe;
+            continue;
+        }
+
+        if arg.eq_ignore_ascii_case("init") {
+            action = Action::RunInit;
+            continue;
+        }
+
         if arg.eq_ignore_ascii_case("help") {
             action = Action::RunHelp;
             continue;
@@ -193,10 +361,17 @@ fn main() {
         action = Action::ParamsHelp;
     }
 
+    // The project configuration lives in the root, so it can only be read after
+    // the parameters are known. Parameters on the command line win over the file.
+    load_config_file(&mut config);
+
     match action {
         Action::ShowConfig => show_config(&config),
         Action::RunHelp => show_help(),
         Action::RunDist => run_dist(&config),
+        Action::RunWatch => run_watch(&config),
+        Action::RunServe => run_serve(&config),
+        Action::RunInit => run_init(&config),
         Action::ParamsHelp => show_params_help(&unrecognized_params),
     }
 }
@@ -212,12 +387,20 @@ fn show_help() {
         Transforms a templated website into a static website, that can be hosted\n\
         by any webserver. Runs this help, if no action has been specified.\n\n\
         Available actions are:\n\
+        \tinit\t\t\tcreates the folders and a few example files for a new\n\
+        \t\t\t\tproject in the root directory\n\
         \tdist\t\t\tbuilds the dist in the specified roots /dist directory\n\
-        \tconfig\t\t\tdumps the config into stdout\n\
+        \twatch\t\t\tbuilds the dist and rebuilds it whenever a file in pages,\n\
+        \t\t\t\tsections, layouts, media, root or the index.html changes\n\
+        \tserve\t\t\tserves the dist directory on http://127.0.0.1:<port>/\n\
+        \t\t\t\twhile rebuilding it like watch; open pages reload\n\
+        \t\t\t\tautomatically after every rebuild\n\
+        \tconfig\t\t\tdumps the config and where each value came from into stdout\n\
         \thelp\t\t\tshows this help\n\n\
         Additional Parameters are:\n\
-        \t--out=<path>\t\toverrides the default output directory; default\n\
-        \t\t\t\tis <root_dir>/dist\n\
+        \t--out=<path>\t\toverrides the output directory relative to root,\n\
+        \t\t\t\tlike the out of static_atoms.toml; default is\n\
+        \t\t\t\t<root_dir>/dist\n\
         \t--in=<path>\t\tselects a path or file to be the input relative to root.\n\
         \t\t\t\tCan be called multiple times; if --in is not set, defaults to \n\
         \t\t\t\t<root_dir>/pages and <root_dir>/index.html\n\
@@ -229,11 +412,26 @@ fn show_help() {
         \t\t\t\tsuccessfully without errors\n\
         \t--verbose\t\toutput extra stuff, for fixing issues\n\
         \t--depth\t\t\tsets the maximum recursion depth. Default is {DEFAULT_MAX_DEPTH}\n\
+        \t--interval=<ms>\t\tsets how often watch looks for changes. Default is\n\
+        \t\t\t\t{DEFAULT_WATCH_INTERVAL}ms\n\
+        \t--port=<port>\t\tsets the port serve listens on. Default is {DEFAULT_PORT}\n\
+        \t--base-url=<url>\tthe address the website is hosted at, like\n\
+        \t\t\t\thttps://example.com. Needed for the sitemap.xml\n\
+        \t--feed=<path>\t\twrites an atom feed.xml of the pages in this folder,\n\
+        \t\t\t\trelative to root. Needs the --base-url aswell\n\
+        \t--permalinks\t\tadds a link to itself to every heading in markdown\n\
         \t--hide-extension\twhen writing the files into output directory, do not use\n\
         \t\t\t\tfile extensions, except for the index files.\n\
         \t-c\t\t\tsame as --clean\n\
         \t-d\t\t\tsame as --dry\n\
         \t-h\t\t\tsame as --hide-extension\n\
+        \t-v\t\t\tsame as --verbose\n\n\
+        Project configuration:\n\
+        If a file called {CONFIG_FILE_NAME} exists in the project root, its values are\n\
+        used as the defaults for out, in, depth, interval, port, base_url,\n\
+        hide_extension, clean, verbose and permalinks. The feed is configured in its\n\
+        [feed] section with folder and title.\n\
+        Parameters passed on the command line always take precedence.\n\
     "
     )
 }
@@ -0,0 +1,356 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Component, Path, PathBuf},
+    sync::{Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::{Configuration, dist::get_dist_path, watch::watch};
+
+static RELOAD_PATH: &str = "/_static_atoms/reload";
+
+/**
+ * Keeps track of how many builds have finished, so that the browsers waiting
+ * on the reload event stream can be notified after every rebuild.
+ */
+#[derive(Default)]
+pub struct LiveReload {
+    generation: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl LiveReload {
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock().unwrap()
+    }
+
+    pub fn notify(&self) {
+        *self.generation.lock().unwrap() += 1;
+        self.changed.notify_all();
+    }
+
+    /**
+     * Blocks until a build newer than `seen` has finished, or the timeout ran out.
+     * Returns whether there is a newer build.
+     */
+    pub fn wait_for_build(&self, seen: u64, timeout: Duration) -> bool {
+        let generation = self.generation.lock().unwrap();
+        let (generation, _) = self
+            .changed
+            .wait_timeout_while(generation, timeout, |generation| *generation <= seen)
+            .unwrap();
+        *generation > seen
+    }
+}
+
+/**
+ * Adds the script, that listens for finished builds to the html page. It's placed
+ * right before the closing body tag, or at the end if there is none.
+ */
+pub fn inject_reload_script(html: &str, generation: u64) -> String {
+    let script = format!(
+        "<script>new EventSource(\"{RELOAD_PATH}?since={generation}\").onmessage = () => location.reload();</script>"
+    );
+    let mut injected = html.to_string();
+    match html.to_ascii_lowercase().rfind("</body>") {
+        Some(index) => injected.insert_str(index, &script),
+        None => injected.push_str(&script),
+    }
+    injected
+}
+
+/**
+ * The content type of a file in the dist. Files without an extension are the
+ * pages written with `--hide-extension`, so they are served as html.
+ */
+pub fn get_mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(OsStr::to_str) {
+        None => "text/html; charset=utf-8",
+        Some(extension) => match extension.to_ascii_lowercase().as_str() {
+            "html" | "htm" => "text/html; charset=utf-8",
+            "css" => "text/css; charset=utf-8",
+            "js" | "mjs" => "text/javascript; charset=utf-8",
+            "json" => "application/json",
+            "xml" => "application/xml",
+            "txt" | "md" => "text/plain; charset=utf-8",
+            "svg" => "image/svg+xml",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "avif" => "image/avif",
+            "ico" => "image/x-icon",
+            "pdf" => "application/pdf",
+            "woff" => "font/woff",
+            "woff2" => "font/woff2",
+            "ttf" => "font/ttf",
+            "otf" => "font/otf",
+            "mp3" => "audio/mpeg",
+            "ogg" => "audio/ogg",
+            "wav" => "audio/wav",
+            "mp4" => "video/mp4",
+            "webm" => "video/webm",
+            "wasm" => "application/wasm",
+            _ => "application/octet-stream",
+        },
+    }
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = input
+                .get((index + 1)..(index + 3))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            index += 3;
+            continue;
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+pub enum Resolved {
+    File(PathBuf),
+    Redirect(String),
+    NotFound,
+}
+
+/**
+ * Maps the path of a request onto a file within the dist directory. Resolves
+ * directories to their index.html and pages without extension to either the
+ * file without extension (`--hide-extension`), or the one with .html appended.
+ */
+pub fn resolve_request_path(dist_path: &Path, url_path: &str) -> Resolved {
+    let url_path = percent_decode(url_path.split(['?', '#']).next().unwrap_or(""));
+    let relative_path = PathBuf::from(url_path.trim_start_matches('/'));
+
+    // Never serve anything outside of the dist directory
+    if relative_path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Resolved::NotFound;
+    }
+
+    let file_path = dist_path.join(&relative_path);
+    if file_path.is_dir() {
+        if !url_path.ends_with('/') {
+            return Resolved::Redirect(url_path + "/");
+        }
+        let index_path = file_path.join("index.html");
+        return if index_path.is_file() {
+            Resolved::File(index_path)
+        } else {
+            Resolved::NotFound
+        };
+    }
+
+    if file_path.is_file() {
+        return Resolved::File(file_path);
+    }
+
+    if file_path.extension().is_none() {
+        let mut html_path = file_path.clone();
+        html_path.add_extension("html");
+        if html_path.is_file() {
+            return Resolved::File(html_path);
+        }
+    }
+
+    Resolved::NotFound
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    include_body: bool,
+) {
+    let mut response = format!("HTTP/1.1 {status}\r\n");
+    for (name, value) in headers {
+        response.push_str(&format!("{name}: {value}\r\n"));
+    }
+    response.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+
+    let mut result = stream.write_all(response.as_bytes());
+    if include_body && result.is_ok() {
+        result = stream.write_all(body);
+    }
+    if result.is_err() {
+        println!("Wasn't able to send the response, the connection was closed early");
+    }
+}
+
+fn handle_reload_events(live_reload: &LiveReload, url_path: &str, mut stream: TcpStream) {
+    let since = url_path
+        .split_once("since=")
+        .and_then(|(_, since)| since.parse().ok())
+        .unwrap_or_else(|| live_reload.generation());
+
+    let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+    if stream.write_all(header.as_bytes()).is_err() {
+        return;
+    }
+
+    // Send a comment every now and then, to find out if the browser is gone
+    loop {
+        if live_reload.wait_for_build(since, Duration::from_secs(15)) {
+            _ = stream.write_all(b"data: reload\n\n");
+            return;
+        }
+        if stream.write_all(b": ping\n\n").is_err() {
+            return;
+        }
+    }
+}
+
+fn handle_connection(
+    config: &Configuration,
+    dist_path: &Path,
+    live_reload: Option<&LiveReload>,
+    mut stream: TcpStream,
+) {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::default();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    // Skip the rest of the request header, it's not needed
+    let mut header_line = String::default();
+    while reader
+        .read_line(&mut header_line)
+        .is_ok_and(|read| read > 2)
+    {
+        header_line.clear();
+    }
+
+    let mut request_parts = request_line.split_whitespace();
+    let (method, url_path) = match (request_parts.next(), request_parts.next()) {
+        (Some(method), Some(url_path)) => (method, url_path),
+        _ => return,
+    };
+
+    let include_body = match method {
+        "GET" => true,
+        "HEAD" => false,
+        _ => {
+            write_response(
+                &mut stream,
+                "405 Method Not Allowed",
+                &[("Allow", "GET, HEAD")],
+                b"",
+                false,
+            );
+            return;
+        }
+    };
+
+    if let Some(live_reload) = live_reload
+        && url_path.starts_with(RELOAD_PATH)
+    {
+        handle_reload_events(live_reload, url_path, stream);
+        return;
+    }
+
+    match resolve_request_path(dist_path, url_path) {
+        Resolved::File(file_path) => match fs::read(&file_path) {
+            Ok(mut contents) => {
+                let mime_type = get_mime_type(&file_path);
+                if let Some(live_reload) = live_reload
+                    && mime_type.starts_with("text/html")
+                {
+                    contents = inject_reload_script(
+                        &String::from_utf8_lossy(&contents),
+                        live_reload.generation(),
+                    )
+                    .into_bytes();
+                }
+
+                if config.verbose {
+                    println!(
+                        "[verbose] {method} {url_path} -> {}",
+                        file_path.to_string_lossy()
+                    );
+                }
+                write_response(
+                    &mut stream,
+                    "200 OK",
+                    &[("Content-Type", mime_type), ("Cache-Control", "no-cache")],
+                    &contents,
+                    include_body,
+                );
+            }
+            Err(_) => write_response(
+                &mut stream,
+                "500 Internal Server Error",
+                &[("Content-Type", "text/plain; charset=utf-8")],
+                b"500 Internal Server Error",
+                include_body,
+            ),
+        },
+        Resolved::Redirect(location) => write_response(
+            &mut stream,
+            "301 Moved Permanently",
+            &[("Location", &location)],
+            b"",
+            include_body,
+        ),
+        Resolved::NotFound => {
+            println!("{method} {url_path} -> 404 Not Found");
+            write_response(
+                &mut stream,
+                "404 Not Found",
+                &[("Content-Type", "text/plain; charset=utf-8")],
+                b"404 Not Found",
+                include_body,
+            )
+        }
+    }
+}
+
+pub fn serve(config: &Configuration, listener: TcpListener, live_reload: Option<&LiveReload>) {
+    let dist_path = get_dist_path(config);
+    thread::scope(|scope| {
+        for stream in listener.incoming().flatten() {
+            let dist_path = &dist_path;
+            scope.spawn(move || handle_connection(config, dist_path, live_reload, stream));
+        }
+    });
+}
+
+pub fn run_serve(config: &Configuration) {
+    let listener = TcpListener::bind(("127.0.0.1", config.port)).unwrap_or_else(|err| {
+        panic!(
+            "Wasn't able to listen on port {}. Is it already in use? {err}",
+            config.port
+        )
+    });
+    println!(
+        "Serving {} at http://127.0.0.1:{}/ Press Ctrl+C to stop.",
+        get_dist_path(config).to_string_lossy(),
+        config.port
+    );
+
+    // Rebuild in the background, and tell the browsers to reload afterwards
+    let live_reload = LiveReload::default();
+    thread::scope(|scope| {
+        scope.spawn(|| watch(config, || live_reload.notify()));
+        serve(config, listener, Some(&live_reload));
+    });
+}
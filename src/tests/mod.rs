@@ -1,6 +1,10 @@
-use std::{env, fs};
+use std::{collections::HashMap, env, fs};
 
-use crate::{Configuration, filetype::FileType};
+use crate::{
+    Configuration,
+    dist::{get_pages, site::Site},
+    filetype::FileType,
+};
 
 #[cfg(test)]
 mod tests_markdown;
@@ -8,6 +12,18 @@ mod tests_markdown;
 #[cfg(test)]
 mod tests_html;
 
+#[cfg(test)]
+mod tests_config;
+
+#[cfg(test)]
+mod tests_watch;
+
+#[cfg(test)]
+mod tests_serve;
+
+#[cfg(test)]
+mod tests_init;
+
 pub fn get_config() -> Configuration {
     get_config_internal("static_atoms_rs_tests", false)
 }
@@ -16,6 +32,17 @@ pub fn get_config_multi() -> Configuration {
     get_config_internal("static_atoms_rs_tests_multi", true)
 }
 
+pub fn get_config_named(name: &str) -> Configuration {
+    get_config_internal(&format!("static_atoms_rs_tests_{name}"), true)
+}
+
+/**
+ * The site of all the pages of the test project, as they are on disk right now
+ */
+pub fn get_site(config: &Configuration) -> Site<'_> {
+    Site::new(config, &get_pages(config))
+}
+
 fn get_config_internal(proj_dir: &str, write: bool) -> Configuration {
     Configuration {
         root: env::temp_dir().join(proj_dir),
@@ -26,6 +53,13 @@ fn get_config_internal(proj_dir: &str, write: bool) -> Configuration {
         verbose: true,
         max_depth: u8::MAX,
         hide_extension: false,
+        watch_interval: 500,
+        port: 8080,
+        base_url: None,
+        feed: None,
+        feed_title: None,
+        permalinks: false,
+        sources: HashMap::default(),
     }
 }
 
@@ -90,6 +124,22 @@ fn create_test_page(
     )
 }
 
+fn create_test_layout(
+    filetype: FileType,
+    config: &Configuration,
+    layout_name: &str,
+    content: &str,
+) {
+    create_embed_objects(
+        filetype,
+        config,
+        vec![],
+        layout_name,
+        content,
+        Some("layouts"),
+    )
+}
+
 fn create_index_page(filetype: FileType, config: &Configuration, content: &str) {
     create_embed_objects(filetype, config, vec![], "index", content, None)
 }
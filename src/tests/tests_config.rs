@@ -0,0 +1,79 @@
+use crate::{
+    ConfigSource,
+    config_file::{apply_config_entries, parse_config_file},
+    dist::get_dist_path,
+    tests::get_config,
+};
+
+#[test]
+fn parse_config_file_entries() {
+    let entries = parse_config_file(
+        "# project settings\nout = \"public\"\nhide-extension = true ; trailing comment\n\n[feed]\ntitle = 'My #1 Blog'\nin = [\"pages/blog\", \"index.html\"]",
+    );
+
+    let flattened: Vec<_> = entries
+        .iter()
+        .map(|entry| (entry.line, entry.key.as_str(), entry.values.join("|")))
+        .collect();
+
+    assert_eq!(
+        flattened,
+        vec![
+            (2, "out", "public".to_string()),
+            (3, "hide_extension", "true".to_string()),
+            (6, "feed.title", "My #1 Blog".to_string()),
+            (7, "feed.in", "pages/blog|index.html".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn apply_config_file_entries() {
+    let mut config = get_config();
+    config.verbose = false;
+    config.hide_extension = false;
+    config.max_depth = 8;
+    let entries = parse_config_file(
+        "hide_extension = true\nverbose = true\ndepth = 3\nclean = maybe\nin = \"pages/a\"\nin = \"pages/b\"\nbase_url = \"https://example.com/\"\n[feed]\nfolder = \"pages/blog\"",
+    );
+    apply_config_entries(&mut config, &entries);
+
+    assert!(config.hide_extension);
+    assert!(config.verbose);
+    assert_eq!(config.max_depth, 3);
+    assert!(!config.clean);
+    assert_eq!(
+        config.input_files,
+        vec![config.root.join("pages/a"), config.root.join("pages/b")]
+    );
+    assert_eq!(config.base_url.as_deref(), Some("https://example.com"));
+    assert_eq!(config.feed, Some(config.root.join("pages/blog")));
+    assert_eq!(config.source("hide_extension"), ConfigSource::File);
+    assert_eq!(config.source("clean"), ConfigSource::Default);
+}
+
+#[test]
+fn cli_overrides_config_file() {
+    let mut config = get_config();
+    config.max_depth = 5;
+    config.sources.insert("depth", ConfigSource::Cli);
+    let entries = parse_config_file("depth = 2\nhide_extension = true");
+    apply_config_entries(&mut config, &entries);
+
+    assert_eq!(config.max_depth, 5);
+    assert_eq!(config.source("depth"), ConfigSource::Cli);
+    assert!(config.hide_extension);
+}
+
+#[test]
+fn resolve_out_against_root() {
+    let mut config = get_config();
+    apply_config_entries(&mut config, &parse_config_file("out = \"public\""));
+    assert_eq!(get_dist_path(&config), config.root.join("public"));
+
+    let mut config = get_config();
+    config.out = Some("public".into());
+    config.sources.insert("out", ConfigSource::Cli);
+    apply_config_entries(&mut config, &parse_config_file("out = \"other\""));
+    assert_eq!(get_dist_path(&config), config.root.join("public"));
+}
@@ -1,10 +1,20 @@
-use std::{collections::HashMap, env, fs::exists, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, exists},
+    path::PathBuf,
+};
 
 use crate::{
-    dist::{build_default_context, get_pages, resolve_tokens_html, run_dist},
+    dist::{
+        build_default_context, build_page_context, get_pages,
+        page::{format_date, split_front_matter},
+        resolve_tokens_html, run_dist,
+    },
     filetype::FileType,
     tests::{
-        create_index_page, create_test_page, create_test_section, get_config, get_config_multi,
+        create_index_page, create_test_layout, create_test_page, create_test_section, get_config,
+        get_config_multi, get_config_named, get_site,
     },
 };
 
@@ -12,7 +22,7 @@ use crate::{
 fn parse_simple() {
     let config = get_config();
     let in_text = "<html><body><p>TEST</p></body></html>".to_owned();
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &HashMap::new());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &HashMap::new());
 
     assert_eq!(in_text, contents);
 }
@@ -29,7 +39,7 @@ fn parse_embed() {
     );
     let in_text = "<html><body><## my_embed></body></html>".to_owned();
     let out_text = "<html><body><p>TEST</p></body></html>".to_owned();
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &HashMap::new());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &HashMap::new());
 
     assert_eq!(out_text, contents);
 }
@@ -46,7 +56,7 @@ fn parse_embed_brackets() {
     );
     let in_text = "<html><body><## my_embed()></body></html>".to_owned();
     let out_text = "<html><body><p>TEST</p></body></html>".to_owned();
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &HashMap::new());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &HashMap::new());
 
     assert_eq!(out_text, contents);
 }
@@ -59,7 +69,7 @@ fn parse_variable_embed() {
     let mut context = HashMap::new();
     context.insert("my_embed".to_owned(), "<p>TEST</p>".to_owned());
 
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &context);
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
 
     assert_eq!(out_text, contents);
 }
@@ -98,7 +108,7 @@ fn parse_folder_embed() {
         "<p>4</p>",
     );
 
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &HashMap::new());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &HashMap::new());
 
     assert_eq!(out_text, contents);
 }
@@ -138,7 +148,7 @@ fn parse_folder_limit_embed() {
         "<p>4</p>",
     );
 
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &HashMap::new());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &HashMap::new());
 
     assert_eq!(out_text, contents);
 }
@@ -173,7 +183,7 @@ fn parse_default_variables() {
 
     let out_text = format!("<html><body>{version}{appname}{applink}{pages}</body></html>");
 
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &context);
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
 
     assert_eq!(out_text, contents);
 }
@@ -191,7 +201,7 @@ fn parse_parametric() {
         "<p><## {var1}><## {var2}></p>",
     );
 
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &HashMap::new());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &HashMap::new());
 
     assert_eq!(out_text, contents);
 }
@@ -212,7 +222,7 @@ fn parse_parametric_edge_cases() {
 
     let mut context = HashMap::new();
     context.insert("var3".to_string(), "v2".to_string());
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &context);
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
 
     assert_eq!(out_text, contents);
 }
@@ -262,3 +272,1053 @@ fn parse_multi_page() {
     assert!(exists(root.join(PathBuf::from("dist/pages/sub/subpage2.html"))).unwrap_or(false));
     assert!(exists(root.join(PathBuf::from("dist/pages/sub/sub2/subpage3.html"))).unwrap_or(false));
 }
+
+#[test]
+fn parse_conditional() {
+    let config = get_config();
+    let in_text = "<## if {lang} == \"de\">Hallo<## else>Hello<## endif>, <## if {name}><## {name}><## else>stranger<## endif>!<## if !{missing}> (no <## if {x}>x<## endif>)<## endif>".to_owned();
+    let mut context = HashMap::new();
+    context.insert("lang".to_owned(), "de".to_owned());
+    context.insert("name".to_owned(), "".to_owned());
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
+    assert_eq!(contents, "Hallo, stranger! (no )");
+
+    context.insert("lang".to_owned(), "en".to_owned());
+    context.insert("name".to_owned(), "Ada".to_owned());
+    context.insert("missing".to_owned(), "true".to_owned());
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
+    assert_eq!(contents, "Hello, Ada!");
+}
+
+#[test]
+fn parse_nested_conditional() {
+    let config = get_config();
+    let in_text = "<ul><## if {a}><li>a</li><## if {b} != 'yes'><li>not b</li><## else><li>b</li><## endif><## else><li>none</li><## endif></ul>".to_owned();
+    let mut context = HashMap::new();
+    context.insert("a".to_owned(), "1".to_owned());
+    context.insert("b".to_owned(), "yes".to_owned());
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
+    assert_eq!(contents, "<ul><li>a</li><li>b</li></ul>");
+
+    context.insert("a".to_owned(), "false".to_owned());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
+    assert_eq!(contents, "<ul><li>none</li></ul>");
+}
+
+#[test]
+fn parse_for_loop() {
+    let config = get_config();
+    let in_text = "<ul><## for tag in {tags}><li<## if {_LAST}> class=\"last\"<## endif>><## {_INDEX}>: <## {tag}></li><## endfor></ul>".to_owned();
+    let out_text = "<ul><li>1: rust</li><li>2: web</li><li class=\"last\">3: static</li></ul>";
+    let mut context = HashMap::new();
+    context.insert("tags".to_owned(), "rust, web,static".to_owned());
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), &in_text, 0, &context);
+    assert_eq!(out_text, contents);
+
+    let in_text =
+        "<## for a in \"x,y\"><## for b in \"1,2\"><## {a}><## {b}> <## endfor><## endfor>";
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &HashMap::new());
+    assert_eq!("x1 x2 y1 y2 ", contents);
+}
+
+#[test]
+fn parse_for_loop_data() {
+    let config = get_config();
+    _ = fs::create_dir_all(config.root.join("data"));
+    _ = fs::write(
+        config.root.join("data/nav.csv"),
+        "label,url\nHome,/\n\"Blog, News\",/pages/blog\n",
+    );
+    let in_text = "<nav><## for link in data:nav><a href=\"<## {link.url}>\"><## {link.label}></a><## endfor></nav>";
+    let out_text = "<nav><a href=\"/\">Home</a><a href=\"/pages/blog\">Blog, News</a></nav>";
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &HashMap::new());
+    assert_eq!(out_text, contents);
+}
+
+#[test]
+fn parse_for_loop_pages() {
+    let config = get_config_named("for_pages");
+    create_test_page(FileType::FileHTML, &config, vec!["docs"], "a", "<p>a</p>");
+    create_test_page(FileType::FileMarkdown, &config, vec!["docs"], "b", "b");
+    create_test_page(FileType::FileHTML, &config, vec![], "other", "<p>c</p>");
+    let in_text = "<## for page in pages:docs><a href=\"<## {page}>\"><## {page.name}></a><## if !{_LAST}>|<## endif><## endfor>";
+    let out_text = "<a href=\"/pages/docs/a.html\">a</a>|<a href=\"/pages/docs/b.html\">b</a>";
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &HashMap::new());
+    assert_eq!(out_text, contents);
+}
+
+#[test]
+fn parse_variable_default() {
+    let config = get_config();
+    let in_text = "<h1><## {title | \"Untitled (draft)\"}></h1><p><## {author | 'Anonymous | Team'}></p><p><## {empty | \"-\"}></p>";
+    let out_text = "<h1>Untitled (draft)</h1><p>Ada</p><p>-</p>";
+    let mut context = HashMap::new();
+    context.insert("author".to_owned(), "Ada".to_owned());
+    context.insert("empty".to_owned(), "".to_owned());
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &context);
+    assert_eq!(out_text, contents);
+}
+
+#[test]
+fn parse_variable_filters() {
+    let config = get_config();
+    let in_text = "<h2 id=\"<## {title | slugify}>\"><## {title | escape}></h2><## {title | upper}>|<## {title | lower | truncate(9)}>|<## {padded | trim}>|<## {missing | \"fallback\" | upper}>|<## {text | markdown}>";
+    let out_text = "<h2 id=\"rust-c-are-friends\">Rust &amp; C++ are &quot;friends&quot;</h2>RUST & C++ ARE \"FRIENDS\"|rust & c+…|x|FALLBACK|<p>some <em>text</em></p>";
+    let mut context = HashMap::new();
+    context.insert("title".to_owned(), "Rust & C++ are \"friends\"".to_owned());
+    context.insert("padded".to_owned(), "  x ".to_owned());
+    context.insert("text".to_owned(), "some *text*".to_owned());
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &context);
+    assert_eq!(out_text, contents);
+}
+
+#[test]
+fn parse_front_matter() {
+    let config = get_config();
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "front_matter_embed",
+        "---\ntitle: \"Front matter: works\"\ncolor: red\n---\n<h1 class=\"<## {color}>\"><## {title}></h1>",
+    );
+    create_test_section(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "front_matter_md",
+        "---\nsubtitle: markdown\n---\n## [## {subtitle}]\n---\nno front matter",
+    );
+    let in_text = "<## front_matter_embed><## front_matter_md><## {color | \"none\"}>";
+    let out_text = "<h1 class=\"red\">Front matter: works</h1><h2 id=\"markdown\">markdown</h2></hr><p>no front matter</p>none";
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &HashMap::new());
+    assert_eq!(out_text, contents);
+
+    // Horizontal lines in markdown are not mistaken for front matter
+    assert!(split_front_matter("---\nthis is not front matter\n---").is_none());
+}
+
+#[test]
+fn skip_draft_pages() {
+    let config = get_config_named("drafts");
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "draft",
+        "---\ndraft: true\n---\n# Not done yet",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "published",
+        "---\ndraft: false\n---\n# Done",
+    );
+
+    let pages = get_pages(&config);
+    assert!(pages.contains(&config.root.join("pages/published.md")));
+    assert!(!pages.contains(&config.root.join("pages/draft.md")));
+}
+
+#[test]
+fn apply_page_layouts() {
+    let config = get_config_named("layouts");
+    create_test_layout(
+        FileType::FileHTML,
+        &config,
+        "base",
+        "<html><body><## {_CONTENT}></body></html>",
+    );
+    create_test_layout(
+        FileType::FileHTML,
+        &config,
+        "post",
+        "---\nlayout: base\n---\n<article><h1><## {title}></h1><## {_CONTENT}></article>",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "post",
+        "---\ntitle: Hello\nlayout: post\n---\nSome *text*",
+    );
+    create_test_page(FileType::FileHTML, &config, vec![], "plain", "<p>plain</p>");
+
+    run_dist(&config);
+
+    let post = fs::read_to_string(config.root.join("dist/pages/post.html")).unwrap();
+    assert_eq!(
+        post,
+        "<html><body><article><h1>Hello</h1><p>Some <em>text</em></p></article></body></html>"
+    );
+    let plain = fs::read_to_string(config.root.join("dist/pages/plain.html")).unwrap();
+    assert_eq!(plain, "<p>plain</p>");
+}
+
+#[test]
+fn parse_block_embed_slots() {
+    let config = get_config();
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "slot_card",
+        "<div class=\"<## {class}>\"><h2><## {title}></h2><## {_SLOT}><footer><## {_SLOT.footer}></footer></div>",
+    );
+    let in_text = "<## slot_card(title=\"Card\" class=\"x\")>\n<p class=\"a\">Hi <## {name}></p>\n<## slot footer><a href=\"/\">Home</a><## endslot>\n<## /slot_card>";
+    let out_text = "<div class=\"x\"><h2>Card</h2>\n<p class=\"a\">Hi Ada</p>\n\n<footer><a href=\"/\">Home</a></footer></div>";
+    let mut context = HashMap::new();
+    context.insert("name".to_owned(), "Ada".to_owned());
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &context);
+    assert_eq!(out_text, contents);
+}
+
+#[test]
+fn parse_nested_block_embeds() {
+    let config = get_config();
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "slot_box",
+        "[<## {_SLOT.head}>|<## {_SLOT}>]",
+    );
+    let in_text = "<## slot_box><## slot head>outer<## endslot><## slot_box><## slot head>inner<## endslot>x<## /slot_box><## /slot_box>";
+    let out_text = "[outer|[inner|x]]";
+
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &HashMap::new());
+    assert_eq!(out_text, contents);
+}
+
+#[test]
+fn build_page_variables() {
+    let mut config = get_config_named("page_context");
+    config.hide_extension = true;
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["docs", "guide"],
+        "setup",
+        "Intro\n\n## Setting things up {#setup}\n",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["docs"],
+        "index",
+        "---\ntitle: Documentation\n---\n<h1>Docs</h1>",
+    );
+
+    let page = config.root.join("pages/docs/guide/setup.md");
+    let context = build_page_context(&get_site(&config), &page, &HashMap::new());
+    assert_eq!(context["_PATH"], "pages/docs/guide/setup.md");
+    assert_eq!(context["_URL"], "/pages/docs/guide/setup");
+    assert_eq!(context["_FILENAME"], "setup");
+    assert_eq!(context["_TITLE"], "Setting things up");
+    assert_eq!(context["_DEPTH_PREFIX"], "../../../");
+    assert_eq!(context["_MODIFIED"].len(), "2025-04-01".len());
+
+    let index = config.root.join("pages/docs/index.html");
+    let context = build_page_context(&get_site(&config), &index, &HashMap::new());
+    assert_eq!(context["_URL"], "/pages/docs");
+    assert_eq!(context["_FILENAME"], "index.html");
+    assert_eq!(context["_TITLE"], "Documentation");
+    assert_eq!(context["_DEPTH_PREFIX"], "../../");
+}
+
+#[test]
+fn format_dates() {
+    let day = std::time::Duration::from_secs(86400);
+    assert_eq!(format_date(std::time::UNIX_EPOCH), "1970-01-01");
+    assert_eq!(
+        format_date(std::time::UNIX_EPOCH + day * 11017),
+        "2000-03-01"
+    );
+    assert_eq!(
+        format_date(std::time::UNIX_EPOCH + day * 20179),
+        "2025-04-01"
+    );
+}
+
+#[test]
+fn build_navigation_variables() {
+    let config = get_config_named("navigation");
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "about",
+        "# About us",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["blog"],
+        "index",
+        "<h1>Blog</h1>",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["blog"],
+        "first",
+        "---\ntitle: First post\n---\nHello",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["docs"],
+        "setup",
+        "<p>setup</p>",
+    );
+    let mut context = build_default_context(&config, &get_pages(&config));
+    context.insert("_URL".to_owned(), "/pages/blog/first.html".to_owned());
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_PAGES:/}>",
+        0,
+        &context,
+    );
+    assert_eq!(
+        contents,
+        "<ul class=\"navigation\"><li><a href=\"/pages/about.html\">About us</a></li><li><a href=\"/pages/blog\">Blog</a><ul><li class=\"active\"><a href=\"/pages/blog/first.html\" aria-current=\"page\">First post</a></li></ul></li><li><span>docs</span><ul><li><a href=\"/pages/docs/setup.html\">setup</a></li></ul></li></ul>"
+    );
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_PAGES:blog}>",
+        0,
+        &context,
+    );
+    assert_eq!(
+        contents,
+        "<ul class=\"navigation\"><li class=\"active\"><a href=\"/pages/blog/first.html\" aria-current=\"page\">First post</a></li><li><a href=\"/pages/blog\">Blog</a></li></ul>"
+    );
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_PAGES:docs/}>",
+        0,
+        &context,
+    );
+    assert_eq!(
+        contents,
+        "<ul class=\"navigation\"><li><a href=\"/pages/docs/setup.html\">setup</a></li></ul>"
+    );
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_PAGES:missing}>",
+        0,
+        &context,
+    );
+    assert_eq!(contents, "<ul class=\"navigation\"></ul>");
+}
+
+#[test]
+fn build_breadcrumbs_variable() {
+    let mut config = get_config_named("breadcrumbs");
+    config.hide_extension = true;
+    create_index_page(FileType::FileHTML, &config, "<h1>Home</h1>");
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["docs"],
+        "index",
+        "# Documentation",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["docs", "guide"],
+        "setup",
+        "---\ntitle: Setup\n---\nText",
+    );
+    let mut context = HashMap::new();
+    context.insert("_PATH".to_owned(), "pages/docs/guide/setup.md".to_owned());
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_BREADCRUMBS}>",
+        0,
+        &context,
+    );
+    assert_eq!(
+        contents,
+        "<ol class=\"breadcrumbs\"><li><a href=\"/\">Home</a></li><li><a href=\"/pages/docs\">Documentation</a></li><li><span>guide</span></li><li><span aria-current=\"page\">Setup</span></li></ol>"
+    );
+
+    context.insert("_PATH".to_owned(), "pages/docs/index.md".to_owned());
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_BREADCRUMBS}>",
+        0,
+        &context,
+    );
+    assert_eq!(
+        contents,
+        "<ol class=\"breadcrumbs\"><li><a href=\"/\">Home</a></li><li><span aria-current=\"page\">Documentation</span></li></ol>"
+    );
+}
+
+#[test]
+fn escape_navigation_titles() {
+    let config = get_config_named("navigation_escape");
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["q&a"],
+        "first",
+        "---\ntitle: Tips & <tricks>\n---\nHello",
+    );
+    let mut context = build_default_context(&config, &get_pages(&config));
+    context.insert("_URL".to_owned(), "/pages/q&a/first.html".to_owned());
+    context.insert("_PATH".to_owned(), "pages/q&a/first.md".to_owned());
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_PAGES:/}>",
+        0,
+        &context,
+    );
+    assert_eq!(
+        contents,
+        "<ul class=\"navigation\"><li><span>q&amp;a</span><ul><li class=\"active\"><a href=\"/pages/q&amp;a/first.html\" aria-current=\"page\">Tips &amp; &lt;tricks&gt;</a></li></ul></li></ul>"
+    );
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## {_BREADCRUMBS}>",
+        0,
+        &context,
+    );
+    assert_eq!(
+        contents,
+        "<ol class=\"breadcrumbs\"><li><span>q&amp;a</span></li><li><span aria-current=\"page\">Tips &amp; &lt;tricks&gt;</span></li></ol>"
+    );
+}
+
+#[test]
+fn write_sitemap_xml() {
+    let mut config = get_config_named("sitemap");
+    config.hide_extension = true;
+    config.base_url = Some("https://example.com".to_owned());
+    create_index_page(FileType::FileHTML, &config, "<h1>Home</h1>");
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["blog"],
+        "first",
+        "---\ndate: 2025-04-01\n---\nHello & welcome",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["blog"],
+        "second",
+        "---\ndate: 2025-04-01\nupdated: 2025-04-02 10:30\n---\nChanged later",
+    );
+
+    run_dist(&config);
+
+    let sitemap = fs::read_to_string(config.root.join("dist/sitemap.xml")).unwrap();
+    assert!(sitemap.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+    assert!(sitemap.contains("<loc>https://example.com/</loc>"));
+    assert!(sitemap.contains(
+        "<loc>https://example.com/pages/blog/first</loc>\n    <lastmod>2025-04-01</lastmod>"
+    ));
+    assert!(sitemap.contains(
+        "<loc>https://example.com/pages/blog/second</loc>\n    <lastmod>2025-04-02T10:30:00Z</lastmod>"
+    ));
+}
+
+#[test]
+fn write_atom_feed() {
+    let mut config = get_config_named("feed");
+    config.base_url = Some("https://example.com".to_owned());
+    config.feed = Some(PathBuf::from("pages/blog"));
+    config.feed_title = Some("Notes & Thoughts".to_owned());
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["blog"],
+        "older",
+        "---\ntitle: Older post\ndate: 2025-01-10\nsummary: The first one\n---\nSome *text*",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["blog"],
+        "newer",
+        "---\ntitle: Newer post\ndate: 2025-03-02 10:30\n---\nMore text",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["blog"],
+        "zoned",
+        "---\ntitle: Zoned post\ndate: 2025-02-01T08:00+0200\n---\nZoned text",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["blog"],
+        "index",
+        "<## {_PAGES:blog}>",
+    );
+    create_test_page(FileType::FileHTML, &config, vec![], "about", "<p>About</p>");
+
+    run_dist(&config);
+
+    let feed = fs::read_to_string(config.root.join("dist/feed.xml")).unwrap();
+    assert!(feed.contains("<title>Notes &amp; Thoughts</title>"));
+    assert!(feed.contains("<updated>2025-03-02T10:30:00Z</updated>\n  <author>"));
+    assert!(
+        feed.contains("<updated>2025-03-02T10:30:00Z</updated>\n    <summary>More text</summary>")
+    );
+    assert!(feed.contains("<updated>2025-02-01T08:00:00+02:00</updated>"));
+    assert!(feed.contains("<entry>\n    <title>Older post</title>\n    <link href=\"https://example.com/pages/blog/older.html\"/>\n    <id>https://example.com/pages/blog/older.html</id>\n    <updated>2025-01-10T00:00:00Z</updated>\n    <summary>The first one</summary>\n    <content type=\"html\">&lt;p&gt;Some &lt;em&gt;text&lt;/em&gt;&lt;/p&gt;</content>"));
+    assert!(feed.find("Newer post").unwrap() < feed.find("Older post").unwrap());
+    assert!(!feed.contains("about.html") && !feed.contains("blog/index"));
+}
+
+#[test]
+fn parse_collection_embed() {
+    let config = get_config_named("collection");
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "post_card",
+        "<a href=\"<## {_URL}>\"><## {_TITLE}></a> <## {_DATE}>: <## {_SUMMARY}>\n",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["posts"],
+        "a_oldest",
+        "---\ndate: 2024-12-24\n---\n# Oldest\n\nFirst paragraph\nstill first.\n\nSecond paragraph",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["posts"],
+        "b_newest",
+        "---\ntitle: Newest\ndate: 2025-03-02\nsummary: Custom summary\n---\n<p>Text</p>",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["posts"],
+        "c_middle",
+        "---\ndate: 2025-01-15\n---\n<h1>Middle</h1>\n<p>Some <em>html</em> text</p>",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["posts"],
+        "d_draft",
+        "---\ndate: 2025-06-01\ndraft: true\n---\n<h1>Draft</h1>",
+    );
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## posts[..2] as post_card>",
+        0,
+        &HashMap::new(),
+    );
+    assert_eq!(
+        contents,
+        "<a href=\"/pages/posts/b_newest.html\">Newest</a> 2025-03-02: Custom summary\n<a href=\"/pages/posts/c_middle.html\">Middle</a> 2025-01-15: Some html text\n"
+    );
+
+    let contents = resolve_tokens_html(
+        "".into(),
+        &get_site(&config),
+        "<## posts[] as post_card>",
+        0,
+        &HashMap::new(),
+    );
+    assert!(contents.ends_with(
+        "<a href=\"/pages/posts/a_oldest.html\">Oldest</a> 2024-12-24: First paragraph still first.\n"
+    ));
+    assert!(!contents.contains("Draft"));
+}
+
+#[test]
+fn write_paginated_pages() {
+    let config = get_config_named("pagination");
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "post_link",
+        "<## {_TITLE}>,",
+    );
+    for (name, date) in [
+        ("one", "2025-01-01"),
+        ("two", "2025-01-02"),
+        ("three", "2025-01-03"),
+        ("four", "2025-01-04"),
+        ("five", "2025-01-05"),
+    ] {
+        create_test_page(
+            FileType::FileMarkdown,
+            &config,
+            vec!["blog"],
+            name,
+            &format!("---\ntitle: {name}\ndate: {date}\n---\nText"),
+        );
+    }
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["blog"],
+        "index",
+        "---\npaginate: blog\nper_page: 2\n---\n<## blog[page] as post_link><## {_PAGE_NUMBER}>/<## {_PAGE_COUNT}>|<## {_PAGE_PREV_URL}>|<## {_PAGE_NEXT_URL}>|<## {_DEPTH_PREFIX}>",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "archive",
+        "---\npaginate: blog\nper_page: 4\n---\n<## {_URL}>|<## {_DEPTH_PREFIX}>",
+    );
+
+    run_dist(&config);
+
+    let read_page = |path: &str| fs::read_to_string(config.root.join("dist").join(path)).unwrap();
+    assert_eq!(
+        read_page("pages/blog/index.html"),
+        "five,four,1/3||/pages/blog/page/2.html|../../"
+    );
+    assert_eq!(
+        read_page("pages/blog/page/2.html"),
+        "three,two,2/3|/pages/blog|/pages/blog/page/3.html|../../../"
+    );
+    assert_eq!(
+        read_page("pages/blog/page/3.html"),
+        "one,3/3|/pages/blog/page/2.html||../../../"
+    );
+    assert_eq!(read_page("pages/archive.html"), "/pages/archive.html|../");
+    assert_eq!(
+        read_page("pages/archive-page/2.html"),
+        "/pages/archive-page/2.html|../../"
+    );
+}
+
+#[test]
+fn paginate_hidden_extension() {
+    let mut config = get_config_named("pagination_hidden");
+    config.hide_extension = true;
+    for name in ["one", "two", "three"] {
+        create_test_page(
+            FileType::FileMarkdown,
+            &config,
+            vec!["blog"],
+            name,
+            &format!("---\ntitle: {name}\n---\nText"),
+        );
+    }
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "archive",
+        "---\npaginate: blog\nper_page: 2\n---\n<## {_URL}>|<## {_PAGE_PREV_URL}>|<## {_PAGE_NEXT_URL}>|<## {_DEPTH_PREFIX}>",
+    );
+
+    run_dist(&config);
+
+    let read_page = |path: &str| fs::read_to_string(config.root.join("dist").join(path)).unwrap();
+    assert_eq!(
+        read_page("pages/archive"),
+        "/pages/archive||/pages/archive-page/2|../"
+    );
+    assert_eq!(
+        read_page("pages/archive-page/2"),
+        "/pages/archive-page/2|/pages/archive||../../"
+    );
+}
+
+#[test]
+fn paginate_selected_input() {
+    let mut config = get_config_named("pagination_input");
+    config.input_files = vec![PathBuf::from("pages/blog/index.html")];
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "post_link",
+        "<## {_TITLE}>,",
+    );
+    for (name, date) in [
+        ("one", "2025-01-01"),
+        ("two", "2025-01-02"),
+        ("three", "2025-01-03"),
+    ] {
+        create_test_page(
+            FileType::FileMarkdown,
+            &config,
+            vec!["blog"],
+            name,
+            &format!("---\ntitle: {name}\ndate: {date}\n---\nText"),
+        );
+    }
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["blog"],
+        "index",
+        "---\npaginate: blog\nper_page: 2\n---\n<## blog[page] as post_link><## {_PAGE_COUNT}>|<## blog[..5] as post_link>",
+    );
+
+    run_dist(&config);
+
+    // Collections list all the pages of their folder, not only the ones being built
+    let read_page = |path: &str| fs::read_to_string(config.root.join("dist").join(path)).unwrap();
+    assert_eq!(
+        read_page("pages/blog/index.html"),
+        "three,two,2|three,two,one,"
+    );
+    assert_eq!(read_page("pages/blog/page/2.html"), "one,2|three,two,one,");
+    assert!(!config.root.join("dist/pages/blog/one.html").exists());
+}
+
+#[test]
+fn paginate_between_siblings() {
+    let config = get_config_named("pagination_siblings");
+    for name in ["one", "two"] {
+        create_test_page(
+            FileType::FileMarkdown,
+            &config,
+            vec!["blog"],
+            name,
+            &format!("---\ntitle: {name}\n---\nText"),
+        );
+    }
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["guide"],
+        "a_intro",
+        "# Intro",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec!["guide"],
+        "b_posts",
+        "---\npaginate: blog\nper_page: 1\n---\n<## {_PREV_TITLE}>|<## {_PREV_URL}>|<## {_PAGE_PREV_URL}>|<## {_PAGE_NEXT_URL}>",
+    );
+
+    run_dist(&config);
+
+    // The links to the siblings stay the same on every page of the pagination
+    let read_page = |path: &str| fs::read_to_string(config.root.join("dist").join(path)).unwrap();
+    assert_eq!(
+        read_page("pages/guide/b_posts.html"),
+        "Intro|/pages/guide/a_intro.html||/pages/guide/b_posts-page/2.html"
+    );
+    assert_eq!(
+        read_page("pages/guide/b_posts-page/2.html"),
+        "Intro|/pages/guide/a_intro.html|/pages/guide/b_posts.html|"
+    );
+}
+
+#[test]
+fn write_tag_pages() {
+    let config = get_config_named("tags");
+    create_test_layout(
+        FileType::FileHTML,
+        &config,
+        "tag",
+        "<h1><## {_TAG}></h1><## {_TAG_PAGES}>",
+    );
+    create_test_layout(FileType::FileHTML, &config, "tags", "<## {_TAGS}>");
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "rust_intro",
+        "---\ntitle: Rust intro\ndate: 2025-01-01\ntags: [Rust, Getting started]\n---\nText",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "rust_traits",
+        "---\ntitle: Traits\ndate: 2025-02-01\ntags: Rust\n---\n<## {_TAGS}>",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "languages",
+        "---\ntitle: Languages\ndate: 2025-03-01\ntags: [C, C#, C++, rust, <b>]\n---\n<## {_TAGS}>",
+    );
+
+    run_dist(&config);
+
+    let read_page = |path: &str| fs::read_to_string(config.root.join("dist").join(path)).unwrap();
+    assert_eq!(
+        read_page("pages/rust_traits.html"),
+        "<ul class=\"tags\"><li><a href=\"/tags/rust.html\">Rust</a></li></ul>"
+    );
+    assert_eq!(
+        read_page("pages/languages.html"),
+        "<ul class=\"tags\"><li><a href=\"/tags/c.html\">C</a></li><li><a href=\"/tags/c-sharp.html\">C#</a></li><li><a href=\"/tags/c-plus-plus.html\">C++</a></li><li><a href=\"/tags/rust.html\">rust</a></li><li><a href=\"/tags/b.html\">&lt;b&gt;</a></li></ul>"
+    );
+    assert_eq!(
+        read_page("tags/rust.html"),
+        "<h1>Rust</h1><ul class=\"tag-pages\"><li><a href=\"/pages/languages.html\">Languages</a></li><li><a href=\"/pages/rust_traits.html\">Traits</a></li><li><a href=\"/pages/rust_intro.html\">Rust intro</a></li></ul>"
+    );
+    assert_eq!(
+        read_page("tags/c-sharp.html"),
+        "<h1>C#</h1><ul class=\"tag-pages\"><li><a href=\"/pages/languages.html\">Languages</a></li></ul>"
+    );
+    assert!(read_page("tags/b.html").starts_with("<h1>&lt;b&gt;</h1>"));
+    assert_eq!(
+        read_page("tags/index.html"),
+        "<ul class=\"tags\"><li><a href=\"/tags/b.html\">&lt;b&gt;</a></li><li><a href=\"/tags/c.html\">C</a></li><li><a href=\"/tags/c-sharp.html\">C#</a></li><li><a href=\"/tags/c-plus-plus.html\">C++</a></li><li><a href=\"/tags/getting-started.html\">Getting started</a></li><li><a href=\"/tags/rust.html\">Rust</a></li></ul>"
+    );
+}
+
+#[test]
+fn write_tag_page_context() {
+    let mut config = get_config_named("tags_context");
+    config.hide_extension = true;
+    create_test_layout(
+        FileType::FileHTML,
+        &config,
+        "tag",
+        "<## {_DEPTH_PREFIX}>style.css|<## {_URL}>|<## {_FILENAME}>|<## {_PATH}>",
+    );
+    create_test_layout(
+        FileType::FileHTML,
+        &config,
+        "tags",
+        "<## {_DEPTH_PREFIX}>style.css|<## {_URL}>",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "post",
+        "---\ntags: Rust\n---\nText",
+    );
+
+    run_dist(&config);
+
+    let read_page = |path: &str| fs::read_to_string(config.root.join("dist").join(path)).unwrap();
+    assert_eq!(
+        read_page("tags/rust"),
+        "../style.css|/tags/rust|rust|tags/rust"
+    );
+    assert_eq!(read_page("tags/index.html"), "../style.css|/tags");
+}
+
+#[test]
+fn build_sibling_navigation() {
+    let config = get_config_named("siblings");
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["guide"],
+        "01_setup",
+        "# Setup",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["guide"],
+        "02_usage",
+        "# Usage",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["guide"],
+        "03_faq",
+        "# FAQ",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["guide"],
+        "index",
+        "# Guide",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["news"],
+        "b",
+        "---\ndate: 2025-01-01\n---\n# First",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec!["news"],
+        "a",
+        "---\ndate: 2025-02-01\n---\n# Second",
+    );
+
+    let default_context = build_default_context(&config, &get_pages(&config));
+    let site = get_site(&config);
+    let context = build_page_context(
+        &site,
+        &config.root.join("pages/guide/02_usage.md"),
+        &default_context,
+    );
+    assert_eq!(context["_PREV_URL"], "/pages/guide/01_setup.html");
+    assert_eq!(context["_PREV_TITLE"], "Setup");
+    assert_eq!(context["_NEXT_URL"], "/pages/guide/03_faq.html");
+    assert_eq!(context["_NEXT_TITLE"], "FAQ");
+
+    let context = build_page_context(
+        &site,
+        &config.root.join("pages/guide/03_faq.md"),
+        &default_context,
+    );
+    assert_eq!(context["_NEXT_URL"], "");
+    assert_eq!(context["_NEXT_TITLE"], "");
+
+    let context = build_page_context(
+        &site,
+        &config.root.join("pages/news/b.md"),
+        &default_context,
+    );
+    assert_eq!(context["_PREV_URL"], "");
+    assert_eq!(context["_NEXT_URL"], "/pages/news/a.html");
+    assert_eq!(context["_NEXT_TITLE"], "Second");
+
+    // Only the pages being built are linked
+    let mut config = config;
+    config.input_files = vec![
+        PathBuf::from("pages/guide/01_setup.md"),
+        PathBuf::from("pages/guide/03_faq.md"),
+    ];
+    let default_context = build_default_context(&config, &get_pages(&config));
+    let site = get_site(&config);
+    let context = build_page_context(
+        &site,
+        &config.root.join("pages/guide/01_setup.md"),
+        &default_context,
+    );
+    assert_eq!(context["_NEXT_URL"], "/pages/guide/03_faq.html");
+}
+
+#[test]
+fn build_table_of_contents() {
+    let config = get_config_named("toc");
+    create_test_layout(
+        FileType::FileHTML,
+        &config,
+        "docs",
+        "<nav><## {_TOC}></nav><main><## {_CONTENT}></main>",
+    );
+    create_test_page(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "guide",
+        "---\nlayout: docs\n---\n# Guide\n## Setup\n## Usage\n### Setup\n# Appendix",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "manual",
+        "<## {_TOC}><h2 id=\"custom\">Intro</h2><h3 class=\"x\">Details &amp; <em>more</em></h3>",
+    );
+
+    run_dist(&config);
+
+    let guide = fs::read_to_string(config.root.join("dist/pages/guide.html")).unwrap();
+    assert_eq!(
+        guide,
+        "<nav><ul class=\"toc\"><li><a href=\"#guide\">Guide</a><ul><li><a href=\"#setup\">Setup</a></li><li><a href=\"#usage\">Usage</a><ul><li><a href=\"#setup-2\">Setup</a></li></ul></li></ul></li><li><a href=\"#appendix\">Appendix</a></li></ul></nav><main><h1 id=\"guide\">Guide</h1><h2 id=\"setup\">Setup</h2><h2 id=\"usage\">Usage</h2><h3 id=\"setup-2\">Setup</h3><h1 id=\"appendix\">Appendix</h1></main>"
+    );
+
+    let manual = fs::read_to_string(config.root.join("dist/pages/manual.html")).unwrap();
+    assert_eq!(
+        manual,
+        "<ul class=\"toc\"><li><a href=\"#custom\">Intro</a><ul><li><a href=\"#details-more\">Details &amp; more</a></li></ul></li></ul><h2 id=\"custom\">Intro</h2><h3 class=\"x\" id=\"details-more\">Details &amp; <em>more</em></h3>"
+    );
+}
+
+#[test]
+fn deduplicate_heading_ids_of_sections() {
+    let mut config = get_config_named("toc_sections");
+    config.permalinks = true;
+    create_test_section(FileType::FileMarkdown, &config, vec![], "first", "## Setup");
+    create_test_section(
+        FileType::FileMarkdown,
+        &config,
+        vec![],
+        "second",
+        "## Setup",
+    );
+    create_test_page(
+        FileType::FileHTML,
+        &config,
+        vec![],
+        "sections",
+        "<## {_TOC}><## first><## second>",
+    );
+
+    run_dist(&config);
+
+    let page = fs::read_to_string(config.root.join("dist/pages/sections.html")).unwrap();
+    assert_eq!(
+        page,
+        "<ul class=\"toc\"><li><a href=\"#setup\">Setup</a></li><li><a href=\"#setup-2\">Setup</a></li></ul><h2 id=\"setup\">Setup<a class=\"permalink\" href=\"#setup\" aria-label=\"Permalink\">#</a></h2><h2 id=\"setup-2\">Setup<a class=\"permalink\" href=\"#setup-2\" aria-label=\"Permalink\">#</a></h2>"
+    );
+}
+
+#[test]
+fn skip_pages_outside_root() {
+    let mut config = get_config_named("outside_root");
+    create_test_page(FileType::FileHTML, &config, vec![], "inside", "<## {_URL}>");
+    let outside_path = env::temp_dir().join("static_atoms_rs_tests_outside_page.html");
+    fs::write(&outside_path, "<## {_URL}>").unwrap();
+    config.input_files = vec![
+        PathBuf::from("pages/inside.html"),
+        outside_path.clone(),
+        PathBuf::from("../static_atoms_rs_tests_outside_page.html"),
+    ];
+
+    assert_eq!(
+        get_pages(&config),
+        vec![config.root.join("pages/inside.html")]
+    );
+
+    run_dist(&config);
+    assert_eq!(fs::read_to_string(&outside_path).unwrap(), "<## {_URL}>");
+    assert_eq!(
+        fs::read_to_string(config.root.join("dist/pages/inside.html")).unwrap(),
+        "/pages/inside.html"
+    );
+}
@@ -0,0 +1,50 @@
+use std::fs;
+
+use crate::{
+    dist::{get_pages, run_dist},
+    init::run_init,
+    tests::get_config_named,
+};
+
+#[test]
+fn init_and_build_project() {
+    let config = get_config_named("init");
+    _ = fs::remove_dir_all(&config.root);
+    run_init(&config);
+
+    for folder in ["pages", "sections", "media", "root"] {
+        assert!(config.root.join(folder).is_dir());
+    }
+
+    run_dist(&config);
+
+    let index = fs::read_to_string(config.root.join("dist/index.html")).unwrap();
+    assert!(index.contains("<h1 id=\"welcome\">Welcome!</h1>"));
+    assert!(index.contains("<h3 id=\"hello-world\">Hello world</h3>"));
+    assert!(index.contains("<h3 id=\"written-in-markdown\">Written in markdown</h3>"));
+    assert!(index.contains("<a href=\"/pages/about.html\">pages/about.html</a>"));
+
+    let about = fs::read_to_string(config.root.join("dist/pages/about.html")).unwrap();
+    assert!(about.contains("<h1 id=\"about-this-website\">About this website</h1>"));
+    assert!(config.root.join("dist/style.css").is_file());
+
+    // Running init again keeps the existing files
+    fs::write(config.root.join("index.html"), "<p>custom</p>").unwrap();
+    run_init(&config);
+    assert_eq!(
+        fs::read_to_string(config.root.join("index.html")).unwrap(),
+        "<p>custom</p>"
+    );
+}
+
+#[test]
+fn build_empty_project() {
+    let mut config = get_config_named("empty");
+    _ = fs::remove_dir_all(&config.root);
+    fs::create_dir_all(&config.root).unwrap();
+    config.clean = true;
+
+    assert_eq!(get_pages(&config), vec![config.root.join("index.html")]);
+    run_dist(&config);
+    assert!(config.root.join("dist").is_dir());
+}
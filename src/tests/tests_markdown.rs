@@ -3,21 +3,21 @@ use std::collections::HashMap;
 use crate::{
     dist::{markdown::resolve_tokens_markdown, resolve_tokens_html},
     filetype::FileType,
-    tests::{create_test_section, get_config},
+    tests::{create_test_section, get_config, get_site},
 };
 
 pub fn test_md_in_out(in_text: &str, out_text: &str) {
     let config = get_config();
     let contents = resolve_tokens_markdown(
         "".into(),
-        &config,
+        &get_site(&config),
         in_text,
         0,
         &HashMap::new(),
         ("<p>", "</p>"),
         false,
     );
-    if out_text.to_owned() != contents.to_owned() {
+    if out_text != contents {
         panic!(
             "Markdown Test Assertion failed:\n***Input***\n\n{in_text}\n\n***Expected***\n\n{out_text}\n\n***Received***\n\n{contents}\n"
         );
@@ -28,7 +28,7 @@ pub fn test_md_in_out(in_text: &str, out_text: &str) {
 fn test_minimal_md() {
     test_md_in_out(
         "# h1 Heading\nSome paragraph afterwards",
-        "<h1>h1 Heading</h1><p>Some paragraph afterwards</p>",
+        "<h1 id=\"h1-heading\">h1 Heading</h1><p>Some paragraph afterwards</p>",
     );
 }
 
@@ -44,7 +44,7 @@ fn test_multiline_paragraph_md() {
 fn test_inbetween_heading_md() {
     test_md_in_out(
         "Some paragraph\n## some heading\nthe last line",
-        "<p>Some paragraph</p><h2>some heading</h2><p>the last line</p>",
+        "<p>Some paragraph</p><h2 id=\"some-heading\">some heading</h2><p>the last line</p>",
     );
 }
 
@@ -52,7 +52,7 @@ fn test_inbetween_heading_md() {
 fn test_emphasis_md() {
     test_md_in_out(
         "this `paragraph` has **multiple** different __inline__ _stylings_. Even *these* ones!\n## Heading with inline **emphasized** component!",
-        "<p>this <code>paragraph</code> has <strong>multiple</strong> different <strong>inline</strong> <em>stylings</em>. Even <em>these</em> ones!</p><h2>Heading with inline <strong>emphasized</strong> component!</h2>",
+        "<p>this <code>paragraph</code> has <strong>multiple</strong> different <strong>inline</strong> <em>stylings</em>. Even <em>these</em> ones!</p><h2 id=\"heading-with-inline-emphasized-component\">Heading with inline <strong>emphasized</strong> component!</h2>",
     );
 }
 
@@ -100,7 +100,7 @@ fn test_blockquotes_md() {
 fn test_formatted_blockquotes_md() {
     test_md_in_out(
         "> ## this is a headline\n >> this is *emphasized*\n > > > this is __bold__",
-        "<blockquote><h2>this is a headline</h2><blockquote><p>this is <em>emphasized</em></p><blockquote><p>this is <strong>bold</strong></p></blockquote></blockquote></blockquote>",
+        "<blockquote><h2 id=\"this-is-a-headline\">this is a headline</h2><blockquote><p>this is <em>emphasized</em></p><blockquote><p>this is <strong>bold</strong></p></blockquote></blockquote></blockquote>",
     );
 }
 
@@ -175,7 +175,7 @@ fn test_mixed_multi_md_html() {
         "* a List with an embed\n* [## embeds_md/one]",
     );
 
-    let contents = resolve_tokens_html("".into(), &config, &in_text, 0, &HashMap::new());
+    let contents = resolve_tokens_html("".into(), &get_site(&config), in_text, 0, &HashMap::new());
 
     assert_eq!(out_text, contents);
 }
@@ -185,7 +185,7 @@ fn test_multi_files_md_txt() {
     let config = get_config();
     let in_text = "this is some text, that has [## embeds_md/included_files_txt] and\n uses [## embeds_md/markdown_files()] for layouting and styling. You can even use variables: \n# [## {CUSTOM_VAR}]";
     let out_text = "<p>this is some text, that has a few included files \
-                            and uses <p><strong>markdown files</strong></p> for layouting and styling. You can even use variables:</p><h1><em>Like this one</em></h1>".to_owned();
+                            and uses <p><strong>markdown files</strong></p> for layouting and styling. You can even use variables:</p><h1 id=\"like-this-one\"><em>Like this one</em></h1>".to_owned();
     let custom_context = {
         let mut context: HashMap<String, String> = HashMap::new();
         context.insert("CUSTOM_VAR".into(), "*Like this one*".into());
@@ -210,8 +210,8 @@ fn test_multi_files_md_txt() {
 
     let contents = resolve_tokens_markdown(
         "".into(),
-        &config,
-        &in_text,
+        &get_site(&config),
+        in_text,
         0,
         &custom_context,
         ("<p>", "</p>"),
@@ -220,3 +220,98 @@ fn test_multi_files_md_txt() {
 
     assert_eq!(out_text, contents);
 }
+
+#[test]
+fn test_conditional_md() {
+    let config = get_config();
+    let custom_context = HashMap::from([("draft".to_string(), "true".to_string())]);
+    let contents = resolve_tokens_markdown(
+        "".into(),
+        &get_site(&config),
+        "[## if {draft}]\n> **Draft:** not published yet\n[## endif]\n# Title",
+        0,
+        &custom_context,
+        ("<p>", "</p>"),
+        false,
+    );
+
+    assert_eq!(
+        contents,
+        "<blockquote><p><strong>Draft:</strong> not published yet</p></blockquote><h1 id=\"title\">Title</h1>"
+    );
+}
+
+#[test]
+fn test_heading_anchors_md() {
+    test_md_in_out(
+        "# Learning C#\n###### Smallest\n## Setup\n## Setup\n## Install {#install-guide}\n## Quoted {#a\"b}\n#hashtag",
+        "<h1 id=\"learning-c\">Learning C#</h1><h6 id=\"smallest\">Smallest</h6><h2 id=\"setup\">Setup</h2><h2 id=\"setup-2\">Setup</h2><h2 id=\"install-guide\">Install</h2><h2 id=\"a&quot;b\">Quoted</h2><p>#hashtag</p>",
+    );
+}
+
+#[test]
+fn test_heading_permalinks_md() {
+    let mut config = get_config();
+    config.permalinks = true;
+    let contents = resolve_tokens_markdown(
+        "".into(),
+        &get_site(&config),
+        "## Getting started",
+        0,
+        &HashMap::new(),
+        ("<p>", "</p>"),
+        false,
+    );
+    assert_eq!(
+        contents,
+        "<h2 id=\"getting-started\">Getting started<a class=\"permalink\" href=\"#getting-started\" aria-label=\"Permalink\">#</a></h2>"
+    );
+}
+
+#[test]
+fn test_table_md() {
+    test_md_in_out(
+        "| Name | Type | Size |\n| :--- | :-: | --: |\n| `a \\| b` | **bold** | 1 |\nc | d\n\nAfter the table",
+        "<table><thead><tr><th align=\"left\">Name</th><th align=\"center\">Type</th><th align=\"right\">Size</th></tr></thead><tbody><tr><td align=\"left\"><code>a | b</code></td><td align=\"center\"><strong>bold</strong></td><td align=\"right\">1</td></tr><tr><td align=\"left\">c</td><td align=\"center\">d</td><td align=\"right\"></td></tr></tbody></table><p>After the table</p>",
+    );
+}
+
+#[test]
+fn test_no_table_md() {
+    test_md_in_out(
+        "a | b\n--- | --- | ---\n\nHeader | only\n-- | --",
+        "<p>a | b --- | --- | ---</p><table><thead><tr><th>Header</th><th>only</th></tr></thead></table>",
+    );
+}
+
+#[test]
+fn test_strikethrough_md() {
+    test_md_in_out(
+        "This is ~~wrong~~ right, but `~~not this~~`",
+        "<p>This is <del>wrong</del> right, but <code>~~not this~~</code></p>",
+    );
+}
+
+#[test]
+fn test_task_list_md() {
+    test_md_in_out(
+        "- [x] Tables\n- [ ] Task lists\n- Just an item",
+        "<ul><li><input type=\"checkbox\" checked disabled> Tables</li><li><input type=\"checkbox\" disabled> Task lists</li><li>Just an item</li></ul>",
+    );
+}
+
+#[test]
+fn test_autolinks_md() {
+    test_md_in_out(
+        "See https://example.com/a_b_c. Or <https://example.com/*star*> and [https://x.org](https://x.org), <a href=\"https://y.org\">https://y.org</a>",
+        "<p>See <a href=\"https://example.com/a_b_c\">https://example.com/a_b_c</a>. Or <a href=\"https://example.com/*star*\">https://example.com/*star*</a> and <a href=\"https://x.org\">https://x.org</a>, <a href=\"https://y.org\">https://y.org</a></p>",
+    );
+}
+
+#[test]
+fn test_autolinks_query_md() {
+    test_md_in_out(
+        "Search https://example.com/?q=a&lang=en or <https://x.org/?a=1&b=2>",
+        "<p>Search <a href=\"https://example.com/?q=a&amp;lang=en\">https://example.com/?q=a&amp;lang=en</a> or <a href=\"https://x.org/?a=1&amp;b=2\">https://x.org/?a=1&amp;b=2</a></p>",
+    );
+}
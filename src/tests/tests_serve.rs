@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    serve::{
+        LiveReload, Resolved, get_mime_type, inject_reload_script, resolve_request_path, serve,
+    },
+    tests::get_config_named,
+};
+
+fn assert_resolves_to(dist_path: &Path, url_path: &str, expected: &str) {
+    match resolve_request_path(dist_path, url_path) {
+        Resolved::File(path) => assert_eq!(path, dist_path.join(expected)),
+        _ => panic!("`{url_path}` should resolve to `{expected}`"),
+    }
+}
+
+#[test]
+fn resolve_served_files() {
+    let config = get_config_named("serve");
+    let dist_path = config.root.join("dist");
+    _ = fs::create_dir_all(dist_path.join("pages/sub"));
+    _ = fs::write(dist_path.join("index.html"), "<p>index</p>");
+    _ = fs::write(dist_path.join("pages/hidden"), "<p>hidden</p>");
+    _ = fs::write(dist_path.join("pages/shown.html"), "<p>shown</p>");
+    _ = fs::write(dist_path.join("pages/sub/index.html"), "<p>sub</p>");
+
+    assert_resolves_to(&dist_path, "/", "index.html");
+    assert_resolves_to(&dist_path, "/pages/hidden", "pages/hidden");
+    assert_resolves_to(&dist_path, "/pages/shown", "pages/shown.html");
+    assert_resolves_to(&dist_path, "/pages/shown.html?query=1", "pages/shown.html");
+    assert_resolves_to(&dist_path, "/pages/sub/", "pages/sub/index.html");
+    assert!(matches!(
+        resolve_request_path(&dist_path, "/pages/sub"),
+        Resolved::Redirect(location) if location == "/pages/sub/"
+    ));
+    assert!(matches!(
+        resolve_request_path(&dist_path, "/pages/../../outside"),
+        Resolved::NotFound
+    ));
+    assert!(matches!(
+        resolve_request_path(&dist_path, "/missing"),
+        Resolved::NotFound
+    ));
+
+    assert_eq!(
+        get_mime_type(&dist_path.join("pages/hidden")),
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(
+        get_mime_type(Path::new("style.css")),
+        "text/css; charset=utf-8"
+    );
+    assert_eq!(get_mime_type(Path::new("logo.PNG")), "image/png");
+}
+
+#[test]
+fn serve_over_http() {
+    let config = get_config_named("serve_http");
+    let dist_path = config.root.join("dist");
+    _ = fs::create_dir_all(&dist_path);
+    _ = fs::write(dist_path.join("index.html"), "<p>served</p>");
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve(&config, listener, None));
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = String::default();
+    stream.read_to_string(&mut response).unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: text/html; charset=utf-8\r\n"));
+    assert!(response.ends_with("\r\n\r\n<p>served</p>"));
+}
+
+#[test]
+fn inject_live_reload() {
+    let injected = inject_reload_script("<html><BODY><p>page</p></BODY></html>", 3);
+    assert_eq!(
+        injected,
+        "<html><BODY><p>page</p><script>new EventSource(\"/_static_atoms/reload?since=3\").onmessage = () => location.reload();</script></BODY></html>"
+    );
+
+    let injected = inject_reload_script("<p>fragment</p>", 0);
+    assert!(injected.starts_with("<p>fragment</p><script>"));
+}
+
+#[test]
+fn notify_live_reload() {
+    let live_reload = LiveReload::default();
+    assert!(!live_reload.wait_for_build(0, Duration::from_millis(10)));
+
+    thread::scope(|scope| {
+        scope.spawn(|| live_reload.notify());
+        assert!(live_reload.wait_for_build(0, Duration::from_secs(5)));
+    });
+    assert_eq!(live_reload.generation(), 1);
+}
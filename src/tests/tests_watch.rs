@@ -0,0 +1,79 @@
+use std::{fs, time::Duration};
+
+use crate::{
+    filetype::FileType,
+    tests::{
+        create_test_layout, create_test_page, create_test_section, get_config, get_config_named,
+    },
+    watch::{changed_paths, take_snapshot, try_run_dist},
+};
+
+#[test]
+fn detect_changed_files() {
+    let config = get_config();
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec!["watched"],
+        "first",
+        "<p>first</p>",
+    );
+    let before = take_snapshot(&config);
+
+    create_test_section(
+        FileType::FileHTML,
+        &config,
+        vec!["watched"],
+        "second",
+        "<p>second</p>",
+    );
+    let after = take_snapshot(&config);
+    let second_path = config.root.join("sections/watched/second.html");
+
+    assert!(changed_paths(&before, &after).contains(&second_path));
+    assert!(changed_paths(&after, &after).is_empty());
+
+    _ = fs::remove_file(&second_path);
+    let removed = take_snapshot(&config);
+    assert!(changed_paths(&after, &removed).contains(&second_path));
+}
+
+#[test]
+fn survive_failed_builds() {
+    let config = get_config_named("failed_build");
+    create_test_page(FileType::FileHTML, &config, vec![], "index", "<p>fixed</p>");
+
+    // A file in place of the dist directory makes the build panic
+    let dist_path = config.root.join("dist");
+    _ = fs::remove_dir_all(&dist_path);
+    _ = fs::write(&dist_path, "");
+    assert!(!try_run_dist(&config));
+
+    _ = fs::remove_file(&dist_path);
+    assert!(try_run_dist(&config));
+    assert!(dist_path.join("pages/index.html").is_file());
+}
+
+#[test]
+fn detect_changed_layouts() {
+    let config = get_config_named("watch_layouts");
+    create_test_layout(FileType::FileHTML, &config, "post", "<main>first</main>");
+    let before = take_snapshot(&config);
+
+    create_test_layout(
+        FileType::FileHTML,
+        &config,
+        "post",
+        "<main>second version</main>",
+    );
+    // Rewrites within the mtime granularity of the filesystem keep the old time
+    let layout_path = config.root.join("layouts/post.html");
+    let modified = before[&layout_path] + Duration::from_secs(2);
+    _ = fs::File::options()
+        .write(true)
+        .open(&layout_path)
+        .and_then(|file| file.set_modified(modified));
+    let after = take_snapshot(&config);
+
+    assert_eq!(changed_paths(&before, &after), vec![layout_path]);
+}
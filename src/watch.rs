@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::{Configuration, dist::run_dist};
+
+static WATCHED_PATHS: [&str; 7] = [
+    "pages",
+    "sections",
+    "layouts",
+    "media",
+    "root",
+    "data",
+    "index.html",
+];
+
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+/**
+ * Collects the modification times of all the files, that influence the output
+ * of the dist. Missing folders are simply skipped, so they can be created later.
+ */
+pub fn take_snapshot(config: &Configuration) -> Snapshot {
+    fn read_layer(path: &Path, snapshot: &mut Snapshot) {
+        let Ok(metadata) = fs::metadata(path) else {
+            return;
+        };
+
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(path) {
+                for entry in entries.flatten() {
+                    read_layer(&entry.path(), snapshot);
+                }
+            }
+        } else if let Ok(modified) = metadata.modified() {
+            snapshot.insert(path.to_path_buf(), modified);
+        }
+    }
+
+    let mut snapshot = Snapshot::default();
+    for watched_path in WATCHED_PATHS {
+        read_layer(&config.root.join(watched_path), &mut snapshot);
+    }
+    snapshot
+}
+
+pub fn changed_paths(old: &Snapshot, new: &Snapshot) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = new
+        .iter()
+        .filter(|(path, modified)| old.get(*path) != Some(modified))
+        .map(|(path, _)| path.clone())
+        .chain(old.keys().filter(|path| !new.contains_key(*path)).cloned())
+        .collect();
+    changed.sort();
+    changed
+}
+
+/**
+ * Builds the dist, but catches any panic while doing so. A broken page shouldn't
+ * end watching, since the next change most likely fixes it.
+ */
+pub fn try_run_dist(config: &Configuration) -> bool {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run_dist(config)));
+    if result.is_err() {
+        println!("Building the dist failed, waiting for the next change ...");
+    }
+    result.is_ok()
+}
+
+/**
+ * Builds the dist once, and then polls the project for changes every
+ * `watch_interval` milliseconds. After every successful rebuild `on_rebuild` is called.
+ */
+pub fn watch(config: &Configuration, mut on_rebuild: impl FnMut()) {
+    let mut snapshot = take_snapshot(config);
+    if try_run_dist(config) {
+        on_rebuild();
+    }
+
+    println!(
+        "Watching for changes every {}ms. Press Ctrl+C to stop.",
+        config.watch_interval
+    );
+
+    loop {
+        thread::sleep(Duration::from_millis(config.watch_interval));
+
+        let new_snapshot = take_snapshot(config);
+        let changed = changed_paths(&snapshot, &new_snapshot);
+        if changed.is_empty() {
+            continue;
+        }
+
+        for path in &changed {
+            let relative_path = path.strip_prefix(&config.root).unwrap_or(path);
+            println!("Changed: {}", relative_path.to_string_lossy());
+        }
+
+        // The snapshot is from before the build, so files changed during the build
+        // are detected on the next poll
+        snapshot = new_snapshot;
+        if try_run_dist(config) {
+            on_rebuild();
+        }
+    }
+}
+
+pub fn run_watch(config: &Configuration) {
+    watch(config, || ());
+}